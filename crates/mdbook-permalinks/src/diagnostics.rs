@@ -13,16 +13,18 @@ use mdbookkit::{
 
 use crate::{
     PREPROCESSOR_NAME,
-    link::{BookPathError, Link, LinkHelp, LinkSpan, LinkState, PathError},
+    link::{BookPathError, LinkHelp, LinkSpan, LinkState, PathError, ResolvedLink},
 };
 
 pub fn link_issue<'a, 'r>(
     root: &'a Url,
     page: &'a Url,
-    link: &'a Link<'r>,
+    link: ResolvedLink<'a, 'r>,
 ) -> Option<IssueReport<'r>> {
     use {BookPathError::*, PathError::*};
 
+    let link = link.get();
+
     let span = match link.span() {
         LinkSpan::Exact(span) => span,
         LinkSpan::Fuzzy(span) => span,
@@ -46,14 +48,8 @@ pub fn link_issue<'a, 'r>(
             };
 
             return IssueReport::if_enabled(IssueLevel::Note).map(|issue| match state {
-                LinkState::Unsupported => issue
-                    .title("the preprocessor does not support these links")
-                    .annotations(vec![
-                        Highlight::span(span.clone())
-                            .kind(AnnotationKind::Context)
-                            .build(),
-                    ])
-                    .build(),
+                LinkState::Unresolved => unreachable!("resolved links have a state"),
+                LinkState::Unsupported => unreachable!("unsupported links are not reported"),
 
                 LinkState::BookLinkChecked => issue
                     .title("these links are valid")
@@ -191,6 +187,21 @@ pub fn link_issue<'a, 'r>(
                     Note::note(format!("the resolved path is {path:?}")),
                 ]);
             }
+
+            BuildOutput => {
+                labels.extend([Highlight::span(span.clone())
+                    .label("link points into the build directory")
+                    .kind(AnnotationKind::Primary)
+                    .build()]);
+
+                notes.extend([
+                    Note::note({
+                        "files in the build directory are generated by mdBook \
+                        and are not part of the book's sources"
+                    }),
+                    Note::note(format!("the resolved path is {path:?}")),
+                ]);
+            }
         }
     } else if let AmbiguousLinkToRoot = error.error {
         labels.extend([Highlight::span(span.clone())
@@ -240,10 +251,34 @@ pub fn link_issue<'a, 'r>(
                 InvalidEncoding => {
                     "link contains characters that are invalid on this system".into()
                 }
+                Unreachable(..) => {
+                    "resolves to a path that is unreachable due to a symlink cycle".into()
+                }
+                SymlinkNotInRepo(..) => {
+                    "resolves through a symlink to a path that is outside of the repository".into()
+                }
                 AmbiguousLinkToRoot => unreachable!(),
                 NoSuchPage(..) => unreachable!(),
             };
 
+        if let SymlinkNotInRepo(path) = &error.error {
+            notes.extend([Note::note(format! {
+                "symlinks were followed from {:?}", path.show()
+            })]);
+        }
+
+        if let Unreachable(cycle) = &error.error {
+            let cycle = std::fmt::from_fn(|f| {
+                for path in cycle {
+                    write!(f, "\n{:?}", path.show())?;
+                }
+                Ok(())
+            });
+            notes.extend([Note::note(format! {
+                "the following symlinks point back to each other:{cycle}"
+            })]);
+        }
+
         if let Some(path) = shortened_path {
             labels.extend([
                 Highlight::primary(span.clone(), format!("{label}:")),
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, fmt::Debug, ops::Range};
+use std::{borrow::Cow, fmt::Debug, ops::Range, path::PathBuf};
 
 use anyhow::{Result, bail};
 use lol_html::{HtmlRewriter, Settings, element, html_content::Element};
@@ -25,6 +25,9 @@ pub struct Link<'a> {
 
 #[derive(Debug, Clone, Copy)]
 pub enum LinkState {
+    /// The link has not gone through resolution yet. [`ResolvedLinks`] never
+    /// contains links in this state.
+    Unresolved,
     Unsupported,
     BookLinkChecked,
     BookLinkUpdated,
@@ -60,6 +63,11 @@ pub enum PathError {
     GitIgnored,
     NoSuchPage(BookPathError),
     AmbiguousLinkToRoot,
+    /// Symlinks along the path form a cycle.
+    Unreachable(Vec<PathBuf>),
+    /// Symlinks along the path lead outside of the repository; holds the path
+    /// before symlinks were resolved.
+    SymlinkNotInRepo(PathBuf),
 }
 
 #[derive(Debug, Clone)]
@@ -68,6 +76,7 @@ pub enum BookPathError {
     MarkdownFileNotIncluded,
     UnexpectedFileExtension,
     NoResourceAtLocation(Vec<LinkError>),
+    BuildOutput,
 }
 
 #[derive(Debug, Clone)]
@@ -188,8 +197,32 @@ enum LinkElem<'a> {
     },
 }
 
+/// A [`LinkSlice`] whose links have all gone through resolution, which is required
+/// for reporting and emitting them.
+#[derive(Debug)]
+pub struct ResolvedLinks<'a>(LinkSlice<'a>);
+
+/// A link in [`ResolvedLinks`].
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedLink<'l, 'a>(&'l Link<'a>);
+
 impl<'a> LinkSlice<'a> {
-    pub fn links_mut(&mut self) -> impl Iterator<Item = &'_ mut Link<'a>> {
+    /// Resolve every link in this slice with `resolve`. Links that `resolve` leaves
+    /// untouched are not supported by the preprocessor.
+    pub fn resolve<F>(mut self, mut resolve: F) -> ResolvedLinks<'a>
+    where
+        F: FnMut(&mut Link<'a>),
+    {
+        for link in self.links_mut() {
+            resolve(link);
+            if let Ok(LinkState::Unresolved) = link.state {
+                link.state = Ok(LinkState::Unsupported);
+            }
+        }
+        ResolvedLinks(self)
+    }
+
+    fn links_mut(&mut self) -> impl Iterator<Item = &'_ mut Link<'a>> {
         self.elem.iter_mut().flat_map(|item| match item {
             LinkElem::Link { link } => std::slice::from_mut(link.as_mut()),
             LinkElem::Html { links, .. } => links,
@@ -205,7 +238,7 @@ impl<'a> LinkSlice<'a> {
         })
     }
 
-    pub fn emit(
+    fn emit(
         self,
     ) -> (
         Patch<'a, impl Iterator<Item = Event<'a>>>,
@@ -248,6 +281,27 @@ impl<'a> LinkSlice<'a> {
     }
 }
 
+impl<'a> ResolvedLinks<'a> {
+    pub fn links(&self) -> impl Iterator<Item = ResolvedLink<'_, 'a>> {
+        self.0.links().map(ResolvedLink)
+    }
+
+    pub fn emit(
+        self,
+    ) -> (
+        Patch<'a, impl Iterator<Item = Event<'a>>>,
+        Option<Range<usize>>,
+    ) {
+        self.0.emit()
+    }
+}
+
+impl<'l, 'a> ResolvedLink<'l, 'a> {
+    pub fn get(self) -> &'l Link<'a> {
+        self.0
+    }
+}
+
 pub struct LinkReader<'a> {
     source: &'a str,
     opened: Option<LinkSlice<'a>>,
@@ -331,7 +385,7 @@ impl<'a> LinkReader<'a> {
 
                 let link = {
                     let link = Link {
-                        state: Ok(LinkState::Unsupported),
+                        state: Ok(LinkState::Unresolved),
                         span: match locate_text(source, &dest) {
                             Some(span) => LinkSpan::Exact(span),
                             None => LinkSpan::Fuzzy(span.clone()),
@@ -431,7 +485,7 @@ impl<'a> LinkSlice<'a> {
                     (_, _) => ContentInterest::Raw,
                 };
                 let link = Link {
-                    state: Ok(LinkState::Unsupported),
+                    state: Ok(LinkState::Unresolved),
                     href: value.into(),
                     interest,
                     title: CowStr::Borrowed(""),
@@ -596,3 +650,57 @@ impl Debug for LinkError {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use mdbook_markdown::pulldown_cmark::Parser;
+
+    use mdbookkit::markdown::patch_stream;
+
+    use crate::Patch;
+
+    use super::{LinkReader, LinkState};
+
+    #[test]
+    fn test_resolve_links() -> Result<()> {
+        let source = "[kept](https://example.org) and [changed](src/lib.rs)\n";
+
+        let mut reader = LinkReader::new(source);
+        let mut patches = vec![];
+        let events = Parser::new(source).into_offset_iter().map(Some);
+        for event in events.chain([None]) {
+            patches.extend(reader.read(event)?);
+        }
+
+        let mut states = vec![];
+        let stream = patches.into_iter().map(|patch| {
+            let links = match patch {
+                Patch::Link(links) => links,
+                Patch::Skip(chunk) => return (Patch::Skip(chunk), None),
+                Patch::SkipOne(elem) => return (Patch::SkipOne(elem), None),
+            };
+            let links = links.resolve(|link| {
+                if link.href() == "src/lib.rs" {
+                    link.permalink("https://example.org/src/lib.rs".into());
+                }
+            });
+            for link in links.links() {
+                states.push(match link.get().state() {
+                    Ok(LinkState::Unsupported) => "unsupported",
+                    Ok(LinkState::Permalink) => "permalink",
+                    state => panic!("unexpected state {state:?}"),
+                });
+            }
+            links.emit()
+        });
+
+        let output = patch_stream(source, stream)?;
+        assert_eq!(
+            output,
+            "[kept](https://example.org) and [changed](https://example.org/src/lib.rs)\n"
+        );
+        assert_eq!(states, ["unsupported", "permalink"]);
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 
 use std::{collections::HashMap, convert::Infallible, ffi::OsStr, fmt::Debug, path::Path};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use data_encoding::BASE64;
 use mdbook_markdown::pulldown_cmark::{Event, Parser};
 use mdbook_preprocessor::{PreprocessorContext, book::Book};
@@ -15,23 +15,27 @@ use mdbookkit::{
     book::{PreprocessorHelper, book_from_stdin},
     config::{BaseDir, validate_config_examples},
     diagnostics::{IssueReporter, SourceCode},
-    emit, emit_debug, emit_error,
+    emit, emit_debug, emit_error, emit_warning,
     env::is_logging,
+    env_var,
     error::{ProgramExit, Show, WithDebugContext, has_severity},
     level_enabled,
-    logging::init_logging,
+    logging::{init_logging, set_quiet},
     markdown::patch_stream,
     plural, ticker, ticker_item,
-    url::{RelativeUrl, UrlUtil},
+    url::{RelativeUrl, UrlFromPath, UrlUtil},
 };
 
 use self::{
     diagnostics::link_issue,
     link::{
-        BookPathError, ContentInterest, Link, LinkError, LinkHelp, LinkReader, LinkState, PathError,
+        BookPathError, ContentInterest, Link, LinkError, LinkHelp, LinkReader, LinkState,
+        PathError, ResolvedLink,
+    },
+    options::{Config, DevModeConfig, DirtyWorktree, LineAnchorContext, Options},
+    vcs::{
+        GitIgnore, LineAnchors, LineOutOfRange, RefOverride, RepoPath, TryRepoPath, VersionControl,
     },
-    options::{Config, DevModeConfig, Options},
-    vcs::{GitIgnore, RepoPath, TryRepoPath, VersionControl},
 };
 
 mod diagnostics;
@@ -40,23 +44,36 @@ mod options;
 mod vcs;
 
 fn main() {
+    let Program {
+        command,
+        quiet,
+        ref_name,
+    } = clap::Parser::parse();
+    set_quiet(quiet);
     init_logging();
     let _span = error_span!({ PREPROCESSOR_NAME }).entered();
-    let Program { command } = clap::Parser::parse();
     match command {
         Some(Command::Supports { .. }) => Ok(()),
         Some(Command::ValidateConfig) => {
             validate_config_examples::<Config>().or_else(emit_error!())
         }
-        None => mdbook(),
+        None => mdbook(ref_name),
     }
     .exit()
 }
 
-fn mdbook() -> Result<(), ()> {
+fn mdbook(ref_name: Option<String>) -> Result<(), ()> {
     let (ctx, mut book) = book_from_stdin(env!("CARGO_PKG_HOMEPAGE")).or_else(emit_error!())?;
 
-    let env = match Environment::new(&ctx, &book) {
+    let ref_override = match ref_name {
+        Some(name) => Some(RefOverride::new(name, "--ref")),
+        // any non-empty value is a ref name, including ones that look falsy like `0`
+        None => (MDBOOK_PERMALINKS_REF.as_deref())
+            .filter(|name| !name.is_empty())
+            .map(|name| RefOverride::new(name.into(), "MDBOOK_PERMALINKS_REF")),
+    };
+
+    let env = match Environment::new(&ctx, &book, ref_override.as_ref()) {
         Ok(Ok(env)) => env,
         Ok(Err(err)) => {
             warn!("{:?}", err.context("preprocessor will be disabled"));
@@ -89,15 +106,20 @@ struct Environment {
     repo: VersionControl,
     book: BookLayout,
     site_url: BaseDir,
+    anchors: Option<LineAnchors>,
     options: Options,
 }
 
 impl Environment {
-    fn new(ctx: &PreprocessorContext, book: &Book) -> Result<Result<Self>> {
+    fn new(
+        ctx: &PreprocessorContext,
+        book: &Book,
+        ref_override: Option<&RefOverride>,
+    ) -> Result<Result<Self>> {
         let config = Config::new(ctx)?;
         debug!("{config:#?}");
 
-        let repo = match VersionControl::try_from_git(&config, &ctx.root) {
+        let repo = match VersionControl::try_from_git(&config, &ctx.root, ref_override) {
             Ok(Ok(repo)) => repo,
             Ok(Err(err)) => return Ok(Err(err.context(GIT_ERROR))),
             Err(err) => return Err(err.context(GIT_ERROR)),
@@ -118,10 +140,22 @@ impl Environment {
             .unwrap_or_default()
             .resolve(&book.base_dir.path);
 
+        let anchors = match options.line_anchor_context {
+            LineAnchorContext::Off => None,
+            LineAnchorContext::Warn => {
+                let path = match options.line_anchor_sidecar.0.take() {
+                    Some(path) => ctx.root.join(path),
+                    None => LineAnchors::default_path(&ctx.book_dir()?, &ctx.page_dir()?),
+                };
+                Some(LineAnchors::load(path)?)
+            }
+        };
+
         Ok(Ok(Self {
             repo,
             book,
             site_url,
+            anchors,
             options,
         }))
     }
@@ -166,15 +200,15 @@ impl Environment {
                 })
                 .flatten()
                 .map(|patch| {
-                    let mut links = match patch {
+                    let links = match patch {
                         Patch::Link(links) => links,
                         Patch::Skip(chunk) => return (Patch::Skip(chunk), None),
                         Patch::SkipOne(elem) => return (Patch::SkipOne(elem), None),
                     };
 
-                    for link in links.links_mut() {
+                    let links = links.resolve(|link| {
                         let Some((resolver, link_url)) = self.triage(&page_url, link) else {
-                            continue;
+                            return;
                         };
 
                         let _span = if !is_logging() {
@@ -194,9 +228,14 @@ impl Environment {
                         .entered();
 
                         resolver.resolve(link_url, link);
+                    });
 
+                    for link in links.links() {
+                        // unsupported links are left as-is and not reported
+                        if let Ok(LinkState::Unsupported) = link.get().state() {
+                            continue;
+                        }
                         report.issues.extend(link_issue(repo_url, &page_url, link));
-
                         stats.count(link);
                     }
 
@@ -228,7 +267,63 @@ impl Environment {
 
         stats.print();
 
-        Ok(())
+        match self.repo.remote_check() {
+            Some(check) => info!("remote check: {check}"),
+            None => debug!("remote check: not enabled"),
+        }
+
+        debug!("looked up {} on the filesystem", plural!(self.repo.probes(), "path"));
+
+        if let Some(anchors) = &self.anchors {
+            anchors.save().or_else(emit_warning!()).ok();
+        }
+
+        self.check_dirty()
+    }
+
+    fn check_dirty(&self) -> Result<(), ()> {
+        if self.options.dirty == DirtyWorktree::Ignore {
+            return Ok(());
+        }
+
+        let changed = match self.repo.uncommitted_changes() {
+            Ok(changed) if changed.is_empty() => return Ok(()),
+            Ok(changed) => changed,
+            Err(err) => {
+                debug!("could not check for uncommitted changes: {err:?}");
+                return Ok(());
+            }
+        };
+
+        const SHOWN: usize = 5;
+
+        let mut files = (changed.iter().take(SHOWN))
+            .map(|path| format!("{path:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if changed.len() > SHOWN {
+            files = format!("{files}, and {} more", changed.len() - SHOWN);
+        }
+
+        let message = format!(
+            "{} linked in the book {} uncommitted changes, permalinks may not match \
+            local content: {files}",
+            plural!(changed.len(), "file"),
+            if changed.len() == 1 { "has" } else { "have" }
+        );
+
+        match self.options.dirty {
+            DirtyWorktree::Warn => {
+                warn!("{message}");
+                warn!("help: commit your changes, or set `dirty = \"ignore\"` to silence this");
+                Ok(())
+            }
+            DirtyWorktree::Error => Err(anyhow!(message))
+                .context("linked files must be committed when `dirty = \"error\"`")
+                .or_else(emit_error!()),
+            DirtyWorktree::Ignore => Ok(()),
+        }
     }
 
     fn triage<'a>(&'a self, page_url: &'a Url, link: &Link<'_>) -> Option<(Resolver<'a>, Url)> {
@@ -314,6 +409,12 @@ impl Resolver<'_> {
             return;
         }
 
+        if self.env.book.is_build_output(&link_url) {
+            debug!("link points into the build directory");
+            link.error(NoSuchPage(BuildOutput).at(link_url));
+            return;
+        }
+
         let orig_url = link_url.clone();
 
         match match match self.location {
@@ -523,6 +624,13 @@ impl Resolver<'_> {
         use LinkResult::*;
 
         if let RepoLink { path } = result {
+            if let Some((_, fragment)) = link.href().split_once('#') {
+                self.check_line_range(&path, fragment);
+                if let Some(anchors) = &self.env.anchors {
+                    self.check_line_anchor(anchors, &path, fragment);
+                }
+            }
+
             let href = if let Some(dev) = &*self.env.options.dev_mode {
                 if let (ContentInterest::Raw, false) = (self.interest, path.is_dir) {
                     match dev.to_embed_link(&path.std_path) {
@@ -549,6 +657,7 @@ impl Resolver<'_> {
             if let Some(href) = href {
                 link.permalink(href);
             } else {
+                self.env.repo.mark_linked(&path);
                 let href = (self.env.repo.scheme()).to_link(&path.relative, self.interest);
                 trace!("rewriting to permalink: {:?}", href.show());
                 link.permalink(href.into());
@@ -593,6 +702,36 @@ impl Resolver<'_> {
         }
     }
 
+    fn check_line_range(&self, path: &RepoPath, fragment: &str) {
+        let LineOutOfRange { end, lines } = match path.check_line_range(fragment) {
+            Ok(Some(range)) => range,
+            Ok(None) => return,
+            Err(err) => {
+                debug!("could not check line range: {err:?}");
+                return;
+            }
+        };
+        let page = self.env.repo.root().as_base().show_path(self.page_url);
+        warn! { "link in {page:?} points to line {end} of {:?}, but the file has only {}",
+        path.relative.show_path(), plural!(lines, "line") };
+    }
+
+    fn check_line_anchor(&self, anchors: &LineAnchors, path: &RepoPath, fragment: &str) {
+        let drift = match anchors.check(path, fragment) {
+            Ok(Some(drift)) => drift,
+            Ok(None) => return,
+            Err(err) => {
+                debug!("could not check line anchor: {err:?}");
+                return;
+            }
+        };
+        let page = self.env.repo.root().as_base().show_path(self.page_url);
+        warn! { "lines at {:?} have changed since the link in {page:?} was recorded \
+        (recorded {}, now {}); check that the link still points to the right lines, \
+        then remove the entry from {:?}",
+        drift.anchor, drift.recorded, drift.current, anchors.path().show() };
+    }
+
     fn ambiguous_link_to_root(&self, link_url: Url, link: &mut Link<'_>) {
         let Environment { repo, .. } = self.env;
 
@@ -681,6 +820,10 @@ struct BookLayout {
     base_url: RelativeUrl,
     source_paths: HashMap<String, Url>,
     public_paths: HashMap<String, Url>,
+    /// Paths under the source directory that are not part of the book. This is only
+    /// relevant when `book.src` is the book root, e.g. `src = "."`.
+    non_book_paths: Vec<String>,
+    build_dir: Option<Url>,
 }
 
 impl BookLayout {
@@ -727,14 +870,46 @@ impl BookLayout {
             .with_debug(&*base_dir.path, "book")
             .context("book is outside of repo")?;
 
+        let book_dir = ctx.book_dir()?;
+        let in_book = |url: &Url| {
+            (base_dir.as_file_url().as_base())
+                .make_relative_scoped(url)
+                .is_some()
+        };
+
+        let build_dir = book_dir.join(&ctx.config.build.build_dir).dir_to_url();
+        let build_dir = if in_book(&build_dir) {
+            debug!("build directory is inside the source directory");
+            Some(build_dir)
+        } else {
+            None
+        };
+
+        let non_book_paths = [book_dir.join("book.toml").file_to_url()]
+            .into_iter()
+            .filter(|url| in_book(url))
+            .filter_map(|url| vcs_root.make_relative(&url))
+            .map(|href| href.encoded_path().to_owned())
+            .collect();
+
         Ok(Self {
             base_dir,
             base_url,
             source_paths,
             public_paths,
+            non_book_paths,
+            build_dir,
         })
     }
 
+    fn is_build_output(&self, url: &Url) -> bool {
+        let Some(build_dir) = &self.build_dir else {
+            return false;
+        };
+        build_dir.as_base().make_relative_scoped(url).is_some()
+            || build_dir.path().strip_suffix('/') == Some(url.path())
+    }
+
     #[instrument(level = "trace", "book_try_file", skip_all, fields(path = ?url.show_path()))]
     fn try_file(&self, url: &RelativeUrl) -> Option<TryBookPath> {
         let root = self.base_url.encoded_path();
@@ -747,6 +922,9 @@ impl BookLayout {
             trace!("public path to {:?}", canonical.show());
             let resolved = canonical.clone().include_after_path(url);
             Some(TryBookPath::PublicPath { resolved })
+        } else if self.non_book_paths.iter().any(|p| p == path) {
+            trace!("not part of the book");
+            None
         } else if path.starts_with(root) || root.strip_prefix(path) == Some("/") {
             debug!("no matching source file");
             Some(TryBookPath::NoSuchPage)
@@ -827,7 +1005,6 @@ impl<'a, E: Iterator<Item = Event<'a>>> Iterator for Patch<'a, E> {
 
 #[derive(Default)]
 struct Statistics {
-    ignored: usize,
     unchanged: usize,
     rewritten: usize,
     permalink: usize,
@@ -836,10 +1013,11 @@ struct Statistics {
 }
 
 impl Statistics {
-    fn count(&mut self, link: &Link<'_>) {
+    fn count(&mut self, link: ResolvedLink<'_, '_>) {
         self.total += 1;
-        match link.state() {
-            Ok(LinkState::Unsupported) => self.ignored += 1,
+        match link.get().state() {
+            Ok(LinkState::Unresolved) => unreachable!("resolved links have a state"),
+            Ok(LinkState::Unsupported) => unreachable!("unsupported links are not counted"),
             Ok(LinkState::BookLinkChecked) => self.unchanged += 1,
             Ok(LinkState::BookLinkUpdated) => self.rewritten += 1,
             Ok(LinkState::Permalink) => self.permalink += 1,
@@ -849,7 +1027,6 @@ impl Statistics {
 
     fn print(&self) {
         let Self {
-            ignored,
             unchanged,
             rewritten,
             permalink,
@@ -862,7 +1039,7 @@ impl Statistics {
             permalink = plural!(permalink, "link"),
             rewritten = plural!(rewritten, "link"),
             error = plural!(error, "has error", "have errors"),
-            unchanged = plural!(unchanged + ignored, "unchanged", "unchanged"),
+            unchanged = plural!(unchanged, "unchanged", "unchanged"),
         );
     }
 }
@@ -871,6 +1048,13 @@ impl Statistics {
 struct Program {
     #[command(subcommand)]
     command: Option<Command>,
+    /// Only show warnings and errors.
+    #[arg(long, short)]
+    quiet: bool,
+    /// Pin permalinks to this ref instead of the one found in the repo.
+    /// Can also be set with `MDBOOK_PERMALINKS_REF`.
+    #[arg(long = "ref", value_name = "REF")]
+    ref_name: Option<String>,
 }
 
 #[derive(clap::Subcommand, Debug, Clone)]
@@ -920,3 +1104,5 @@ macro_rules! PREPROCESSOR_NAME {
 }
 
 static PREPROCESSOR_NAME: &str = PREPROCESSOR_NAME!();
+
+env_var!(MDBOOK_PERMALINKS_REF);
@@ -1,4 +1,4 @@
-use std::ops::Deref;
+use std::{ops::Deref, path::PathBuf, time::Duration};
 
 use anyhow::{Context, Result};
 use mdbook_preprocessor::PreprocessorContext;
@@ -13,7 +13,7 @@ use mdbookkit::{
     impl_deserialize_from_str, try2,
 };
 
-use crate::PREPROCESSOR_NAME;
+use crate::{PREPROCESSOR_NAME, vcs::parse_template};
 
 #[derive(Debug, Default)]
 pub struct Config {
@@ -66,13 +66,21 @@ impl TryFrom<BookToml<'_>> for Config {
 #[derive(Deserialize, Debug, Default)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct Options {
-    #[serde(default, deserialize_with = "value_shorthand::<Url, _, _>")]
+    #[serde(default, deserialize_with = "value_shorthand::<RepoUrlTemplate, _, _>")]
     pub repo_url_template: TemplateConfig,
     #[serde(default)]
     pub always_link: Vec<String>,
     #[serde(default)]
     pub remote_name: Option<String>,
     #[serde(default)]
+    pub platform: Option<Platform>,
+    #[serde(default, rename = "ref")]
+    pub ref_kind: RefKind,
+    #[serde(default)]
+    pub dirty: DirtyWorktree,
+    #[serde(default, deserialize_with = "value_shorthand::<bool, _, _>")]
+    pub verify_remote: VerifyRemote,
+    #[serde(default)]
     #[serde(deserialize_with = "via::<UnstableFeature<ValueShorthand<bool, _>>, _, _>")]
     pub dev_mode: DevMode,
     #[serde(default)]
@@ -84,12 +92,18 @@ pub struct Options {
     #[serde(deserialize_with = "via::<UnstableFeature<SiteUrl>, _, _>")]
     // compat option for private use to avoid building for HTML
     pub site_url: SiteUrl,
+    #[serde(default)]
+    #[serde(deserialize_with = "via::<UnstableFeature<LineAnchorContext>, _, _>")]
+    pub line_anchor_context: LineAnchorContext,
+    #[serde(default)]
+    #[serde(deserialize_with = "via::<UnstableFeature<LineAnchorSidecar>, _, _>")]
+    pub line_anchor_sidecar: LineAnchorSidecar,
 }
 
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct TemplateConfig {
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_template")]
     pub template: Option<Url>,
     #[serde(default)]
     pub params: Option<PathParams>,
@@ -106,6 +120,8 @@ pub struct PathParams {
     pub commit: Vec<String>,
     #[serde(default, deserialize_with = "value_or_vec1")]
     pub tag: Vec<String>,
+    #[serde(default, deserialize_with = "value_or_vec1")]
+    pub branch: Vec<String>,
 }
 
 impl Default for PathParams {
@@ -115,12 +131,19 @@ impl Default for PathParams {
             raw: vec!["raw".into()],
             commit: vec!["commit".into()],
             tag: vec!["tag".into()],
+            branch: vec!["branch".into()],
         }
     }
 }
 
-impl From<Url> for TemplateConfig {
-    fn from(value: Url) -> Self {
+pub struct RepoUrlTemplate(Url);
+
+impl_deserialize_from_str!(RepoUrlTemplate, "a URL template", |s| {
+    Ok(Self(parse_template(s)?))
+});
+
+impl From<RepoUrlTemplate> for TemplateConfig {
+    fn from(RepoUrlTemplate(value): RepoUrlTemplate) -> Self {
         Self {
             template: Some(value),
             params: Default::default(),
@@ -128,6 +151,115 @@ impl From<Url> for TemplateConfig {
     }
 }
 
+fn deserialize_template<'de, D>(deserializer: D) -> Result<Option<Url>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Some(RepoUrlTemplate::deserialize(deserializer)?.0))
+}
+
+/// Git forges with built-in permalink formats.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Platform {
+    Github,
+    Gitlab,
+    Bitbucket,
+    #[serde(alias = "forgejo", alias = "codeberg")]
+    Gitea,
+    #[serde(alias = "azure-devops")]
+    Azure,
+    #[serde(alias = "srht")]
+    Sourcehut,
+}
+
+/// Which ref to pin permalinks to.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RefKind {
+    #[default]
+    TagOrCommit,
+    Commit,
+    Branch,
+    TagOrBranch,
+}
+
+/// What to do when linked files have uncommitted changes.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DirtyWorktree {
+    #[default]
+    Warn,
+    Error,
+    Ignore,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(from = "VerifyRemoteConfig")]
+pub struct VerifyRemote(Option<VerifyRemoteConfig>);
+
+impl Deref for VerifyRemote {
+    type Target = Option<VerifyRemoteConfig>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<bool> for VerifyRemote {
+    fn from(value: bool) -> Self {
+        if value {
+            Self(Some(Default::default()))
+        } else {
+            Self(None)
+        }
+    }
+}
+
+impl From<VerifyRemoteConfig> for VerifyRemote {
+    fn from(value: VerifyRemoteConfig) -> Self {
+        Self(Some(value))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct VerifyRemoteConfig {
+    #[serde(default)]
+    pub fallback: RemoteFallback,
+    /// Seconds to wait for the remote before giving up.
+    #[serde(default = "VerifyRemoteConfig::default_timeout")]
+    pub timeout: u64,
+}
+
+impl Default for VerifyRemoteConfig {
+    fn default() -> Self {
+        Self {
+            fallback: Default::default(),
+            timeout: Self::default_timeout(),
+        }
+    }
+}
+
+impl VerifyRemoteConfig {
+    fn default_timeout() -> u64 {
+        5
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout)
+    }
+}
+
+/// What to do when the resolved ref is not found on the remote.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RemoteFallback {
+    #[default]
+    Warn,
+    Branch,
+}
+
 #[derive(Deserialize, Debug, Default, Clone)]
 #[serde(from = "DevModeConfig")]
 pub struct DevMode(Option<DevModeConfig>);
@@ -218,3 +350,26 @@ impl From<UnstableFeature<SiteUrl>> for SiteUrl {
         value
     }
 }
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineAnchorContext {
+    #[default]
+    Off,
+    Warn,
+}
+
+impl From<UnstableFeature<LineAnchorContext>> for LineAnchorContext {
+    fn from(UnstableFeature(value): UnstableFeature<LineAnchorContext>) -> Self {
+        value
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct LineAnchorSidecar(pub Option<PathBuf>);
+
+impl From<UnstableFeature<LineAnchorSidecar>> for LineAnchorSidecar {
+    fn from(UnstableFeature(value): UnstableFeature<LineAnchorSidecar>) -> Self {
+        value
+    }
+}
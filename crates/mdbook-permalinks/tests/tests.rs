@@ -179,6 +179,45 @@ test_in_temp_dir![
     }
 ];
 
+test_in_temp_dir![
+    git_dirty(
+        exit(0),
+        env = ["MDBOOK_LOG" = "warn,mdbook_permalinks=info"]
+    ),
+    |book| {
+        git_commit_all(book)?;
+        std::fs::write(book.book_dir().join("linked.txt"), "modified")?;
+        std::fs::write(book.book_dir().join("unlinked.txt"), "modified")?;
+        std::fs::write(book.book_dir().join("data/untracked.txt"), "")?;
+        Ok(())
+    }
+];
+
+test_in_temp_dir![
+    git_dirty_clean(
+        exit(0),
+        env = ["MDBOOK_LOG" = "warn,mdbook_permalinks=info"]
+    ),
+    |book| { git_commit_all(book) }
+];
+
+fn git_commit_all(book: &TestRoot<'static>) -> Result<()> {
+    run!(book, "git", "init");
+    #[rustfmt::skip]
+    run!(book, "git", "remote", "add", "origin", "https://github.com/lorem/ipsum.git");
+    run!(book, "git", "add", "--all");
+    #[rustfmt::skip]
+    run!(
+        book,
+        ["GIT_AUTHOR_NAME" = "me"],
+        ["GIT_AUTHOR_EMAIL" = "me@example.org"],
+        ["GIT_COMMITTER_NAME" = "me"],
+        ["GIT_COMMITTER_EMAIL" = "me@example.org"],
+        "git", "commit", "--message", "init"
+    );
+    Ok(())
+}
+
 fn temp_dir_test<F>(mut book: TestBook, setup: F) -> Result<()>
 where
     F: for<'a> FnOnce(&'a TestRoot<'static>) -> Result<()>,
@@ -30,7 +30,8 @@ use mdbookkit::{
 use crate::{
     diagnostics::DiagnosticNotes,
     options::{
-        BuildConfigResolved, BuildOptions, Builder, PackageSelector, PackageSpec, WorkspaceMember,
+        BuildConfigResolved, BuildOptions, Builder, FeatureSelection, PackageSelector, PackageSpec,
+        WorkspaceMember,
     },
     subprocess::CommandRunnerUtil,
     tracker::LinkTracker,
@@ -128,6 +129,19 @@ fn run_builder(
 
     debug!("resolved preludes: {preludes:#?}");
 
+    if !features.all_features() {
+        let disabled = resolve_disabled_features(&metadata, &packages, &features);
+        debug!("features not enabled: {disabled:#?}");
+        tracker.notes().mark_features_not_enabled(disabled);
+    }
+
+    if docs_rs.is_none()
+        && let Some(package) = find_docs_rs_features(&metadata, &packages)
+    {
+        debug!("package {package:?} specifies features for docs.rs, which are not inherited");
+        tracker.notes().mark_docs_rs_features(package);
+    }
+
     let rustflags = if !rustc_args.is_empty() {
         Some(into_cargo_config("build.rustflags", rustc_args))
     } else {
@@ -955,6 +969,66 @@ fn resolve_prelude(
     Some(lib)
 }
 
+fn resolve_disabled_features(
+    metadata: &cargo_metadata::Metadata,
+    packages: &PackageResolution,
+    features: &FeatureSelection,
+) -> Vec<String> {
+    let Some(resolve) = &metadata.resolve else {
+        return vec![];
+    };
+
+    let is_requested = |pkg: &cargo_metadata::Package, feature: &str| {
+        features.list().iter().any(|requested| {
+            requested == feature || *requested == format!("{}/{feature}", pkg.name)
+        })
+    };
+
+    let mut disabled = vec![];
+
+    for pkg in metadata.workspace_packages() {
+        if !packages.contains(pkg) {
+            continue;
+        }
+        let Some(node) = resolve.nodes.iter().find(|node| node.id == pkg.id) else {
+            continue;
+        };
+        for feature in pkg.features.keys() {
+            if feature == "default"
+                || is_requested(pkg, feature)
+                || node.features.iter().any(|f| **f == *feature)
+            {
+                continue;
+            }
+            disabled.push(format!("{}/{feature}", pkg.name));
+        }
+    }
+
+    disabled
+}
+
+/// Find a selected workspace package that enables features in its
+/// `[package.metadata.docs.rs]` table.
+fn find_docs_rs_features(
+    metadata: &cargo_metadata::Metadata,
+    packages: &PackageResolution,
+) -> Option<String> {
+    metadata.workspace_packages().into_iter().find_map(|pkg| {
+        if !packages.contains(pkg) {
+            return None;
+        }
+        let docs_rs = pkg.metadata.get("docs")?.get("rs")?.as_object()?;
+        if ["features", "all-features"]
+            .iter()
+            .any(|key| docs_rs.contains_key(*key))
+        {
+            Some(pkg.name.to_string())
+        } else {
+            None
+        }
+    })
+}
+
 #[derive(Default)]
 struct PackageResolution(BTreeSet<(String, String)>);
 
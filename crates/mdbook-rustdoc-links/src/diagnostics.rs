@@ -105,6 +105,8 @@ pub struct DiagnosticNotes {
     options_specified: BTreeSet<&'static str>,
     preludes_derived: Vec<String>,
     preludes_not_derived: Option<&'static str>,
+    features_not_enabled: Vec<String>,
+    docs_rs_features: Option<String>,
     visited: VisitedNotes,
 }
 
@@ -113,6 +115,7 @@ struct VisitedNotes {
     options_specified: bool,
     preludes_derived: bool,
     preludes_not_derived: bool,
+    features_not_enabled: bool,
 }
 
 impl DiagnosticNotes {
@@ -172,6 +175,30 @@ impl DiagnosticNotes {
         Some(note)
     }
 
+    pub fn note_features_not_enabled(&mut self) -> Option<String> {
+        if self.features_not_enabled.is_empty() || self.visited.features_not_enabled {
+            return None;
+        }
+        self.visited.features_not_enabled = true;
+        let features = self
+            .features_not_enabled
+            .iter()
+            .map(|feature| format!("- {feature}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut note = format! {
+            "the following features are not enabled; if this item is gated behind \
+            a feature,\nadd the feature to the `build.features` option:\n{features}"
+        };
+        if let Some(package) = &self.docs_rs_features {
+            note.push_str(&format! {
+                "\nor set `build.docs-rs = true` to use the features that \
+                package `{package}`\nenables in its [package.metadata.docs.rs] table"
+            });
+        }
+        Some(note)
+    }
+
     pub fn mark_option_specified(&mut self, builder: &Builder) {
         let Builder { targets, options } = builder;
         let BuildOptions {
@@ -243,6 +270,14 @@ impl DiagnosticNotes {
     pub fn mark_preludes_not_derived_because(&mut self, reason: &'static str) {
         self.preludes_not_derived = Some(reason)
     }
+
+    pub fn mark_features_not_enabled(&mut self, features: Vec<String>) {
+        self.features_not_enabled = features
+    }
+
+    pub fn mark_docs_rs_features(&mut self, package: String) {
+        self.docs_rs_features = Some(package)
+    }
 }
 
 #[macro_export]
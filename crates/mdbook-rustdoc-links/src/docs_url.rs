@@ -0,0 +1,372 @@
+use url::Url;
+
+/// Try to recover the item path that a docs.rs or doc.rust-lang.org page documents,
+/// such that it can be resolved again as an intra-doc link.
+///
+/// Returns [`None`] if the URL is not on a known docs host, or if it is not
+/// the page of an item.
+pub fn item_path_from_url(url: &Url) -> Option<String> {
+    if !matches!(url.scheme(), "https" | "http") {
+        return None;
+    }
+
+    let mut segments = url.path_segments()?.peekable();
+
+    match url.host_str()? {
+        "docs.rs" => {
+            let _krate = segments.next().filter(|s| *s != "crate")?;
+            let _version = segments.next()?;
+            // docs.rs optionally includes the target triple, which,
+            // unlike lib names, always contains `-`
+            if segments.peek()?.contains('-') {
+                segments.next();
+            }
+        }
+        "doc.rust-lang.org" => {
+            if let Some(&channel) = segments.peek()
+                && (matches!(channel, "stable" | "beta" | "nightly")
+                    || channel.starts_with(|c: char| c.is_ascii_digit()))
+            {
+                segments.next();
+            }
+            if !matches!(
+                *segments.peek()?,
+                "std" | "core" | "alloc" | "proc_macro" | "test"
+            ) {
+                return None;
+            }
+        }
+        _ => return None,
+    }
+
+    let segments = segments.collect::<Vec<_>>();
+    let (page, modules) = segments.split_last()?;
+
+    if modules.is_empty() || !modules.iter().all(|m| is_ident(m)) {
+        return None;
+    }
+
+    let mut path = modules.join("::");
+
+    let disambiguator = if page.is_empty() || *page == "index.html" {
+        "mod@"
+    } else {
+        let (kind, name) = page.strip_suffix(".html")?.split_once('.')?;
+        if !is_ident(name) {
+            return None;
+        }
+        match kind {
+            "primitive" => {
+                path = name.to_owned();
+                "prim@"
+            }
+            "macro" => {
+                path = format!("{path}::{name}!");
+                ""
+            }
+            kind => {
+                path = format!("{path}::{name}");
+                item_disambiguator(kind)?
+            }
+        }
+    };
+
+    match url.fragment() {
+        None | Some("") => Some(format!("{disambiguator}{path}")),
+        Some(fragment) => {
+            if let Some((kind, name)) = fragment.split_once('.')
+                && is_associated_item(kind)
+            {
+                // associated items cannot have disambiguators,
+                // and the parent item is unambiguous anyway
+                if !is_ident(name) {
+                    return None;
+                }
+                Some(format!("{path}::{name}"))
+            } else {
+                Some(format!("{disambiguator}{path}#{fragment}"))
+            }
+        }
+    }
+}
+
+/// Normalize a URL on the web, such that links to the same page are always written the
+/// same way.
+///
+/// Trailing `index.html` and repeated `/` are removed from the path, and links to
+/// docs.rs and doc.rust-lang.org use `https`. Hosts are already lowercased by [`Url`].
+pub fn normalize_url(mut url: Url) -> Url {
+    if !matches!(url.scheme(), "https" | "http") {
+        return url;
+    }
+
+    if url.scheme() == "http" && matches!(url.host_str(), Some("docs.rs" | "doc.rust-lang.org")) {
+        url.set_scheme("https").ok();
+    }
+
+    let mut path = String::with_capacity(url.path().len());
+    for c in url.path().chars() {
+        if c == '/' && path.ends_with('/') {
+            continue;
+        }
+        path.push(c);
+    }
+    if path.ends_with("/index.html") {
+        path.truncate(path.len() - "index.html".len());
+    }
+    url.set_path(&path);
+
+    url
+}
+
+/// Make a link to doc.rust-lang.org point to the docs for a specific Rust release,
+/// replacing the release or channel that is already in the URL, if any.
+///
+/// `version` may be a channel, or a release as either `MAJOR.MINOR.PATCH` or `MAJOR.MINOR`.
+/// URLs to pages that are not docs for the standard library crates are left unchanged.
+pub fn pin_rust_version(mut url: Url, version: &str) -> Url {
+    if !matches!(url.scheme(), "https" | "http") || url.host_str() != Some("doc.rust-lang.org") {
+        return url;
+    }
+
+    let Some(mut segments) = url.path_segments().map(|s| s.collect::<Vec<_>>()) else {
+        return url;
+    };
+
+    let is_std_crate = |segment: &str| {
+        matches!(segment, "std" | "core" | "alloc" | "proc_macro" | "test")
+    };
+
+    let is_version = |segment: &str| {
+        matches!(segment.split('.').count(), 2 | 3)
+            && segment.split('.').all(|n| n.parse::<u32>().is_ok())
+    };
+
+    let is_release = |segment: &str| {
+        matches!(segment, "stable" | "beta" | "nightly") || is_version(segment)
+    };
+
+    // docs are only published under full version numbers, so `1.80` becomes `1.80.0`
+    let version = if is_version(version) && version.split('.').count() == 2 {
+        &*format!("{version}.0")
+    } else {
+        version
+    };
+
+    match segments.as_slice() {
+        [first, second, ..] if is_release(first) && is_std_crate(second) => {
+            segments[0] = version;
+        }
+        [first, ..] if is_std_crate(first) => {
+            segments.insert(0, version);
+        }
+        _ => return url,
+    }
+
+    let path = segments.join("/");
+    url.set_path(&path);
+    url
+}
+
+/// <https://doc.rust-lang.org/rustdoc/write-documentation/linking-to-items-by-name.html#namespaces-and-disambiguators>
+fn item_disambiguator(kind: &str) -> Option<&'static str> {
+    match kind {
+        "struct" => Some("struct@"),
+        "enum" => Some("enum@"),
+        "trait" => Some("trait@"),
+        "traitalias" => Some("trait@"),
+        "union" => Some("union@"),
+        "fn" => Some("fn@"),
+        "type" => Some("type@"),
+        "constant" => Some("const@"),
+        "static" => Some("static@"),
+        "derive" => Some("derive@"),
+        "attr" => Some("attr@"),
+        _ => None,
+    }
+}
+
+fn is_associated_item(kind: &str) -> bool {
+    matches!(
+        kind,
+        "method"
+            | "tymethod"
+            | "variant"
+            | "structfield"
+            | "associatedconstant"
+            | "associatedtype"
+    )
+}
+
+fn is_ident(name: &str) -> bool {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{item_path_from_url, normalize_url, pin_rust_version};
+
+    #[test]
+    fn test_item_path_from_url() {
+        for (url, expected) in [
+            (
+                "https://docs.rs/serde/1.0.0/serde/trait.Serialize.html",
+                Some("trait@serde::Serialize"),
+            ),
+            (
+                "https://docs.rs/serde/1.0.0/serde/derive.Serialize.html",
+                Some("derive@serde::Serialize"),
+            ),
+            (
+                "https://docs.rs/serde/latest/serde/de/index.html",
+                Some("mod@serde::de"),
+            ),
+            ("https://docs.rs/serde/latest/serde/", Some("mod@serde")),
+            (
+                "https://docs.rs/serde_json/1.0.0/serde_json/macro.json.html",
+                Some("serde_json::json!"),
+            ),
+            (
+                "https://docs.rs/tokio/1.0.0/x86_64-unknown-linux-gnu/tokio/fn.spawn.html",
+                Some("fn@tokio::spawn"),
+            ),
+            (
+                "https://docs.rs/serde/1.0.0/serde/trait.Serializer.html#tymethod.serialize_str",
+                Some("serde::Serializer::serialize_str"),
+            ),
+            (
+                "https://docs.rs/serde/1.0.0/serde/trait.Serialize.html#examples",
+                Some("trait@serde::Serialize#examples"),
+            ),
+            (
+                "https://doc.rust-lang.org/std/vec/struct.Vec.html",
+                Some("struct@std::vec::Vec"),
+            ),
+            (
+                "https://doc.rust-lang.org/stable/std/vec/struct.Vec.html#method.push",
+                Some("std::vec::Vec::push"),
+            ),
+            (
+                "https://doc.rust-lang.org/1.74.0/core/option/enum.Option.html#variant.Some",
+                Some("core::option::Option::Some"),
+            ),
+            (
+                "https://doc.rust-lang.org/nightly/std/primitive.u8.html",
+                Some("prim@u8"),
+            ),
+            (
+                "https://doc.rust-lang.org/std/keyword.match.html",
+                None,
+            ),
+            ("https://doc.rust-lang.org/book/ch01-00.html", None),
+            ("https://docs.rs/crate/serde/1.0.0", None),
+            ("https://docs.rs/serde/1.0.0/serde/all.html", None),
+            ("https://example.org/serde/struct.Foo.html", None),
+        ] {
+            let url = url.parse().unwrap();
+            assert_eq!(item_path_from_url(&url).as_deref(), expected, "{url}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        for (url, expected) in [
+            (
+                "https://docs.rs/serde/1.0.200/serde/index.html",
+                "https://docs.rs/serde/1.0.200/serde/",
+            ),
+            (
+                "https://docs.rs/serde/1.0.200/serde/",
+                "https://docs.rs/serde/1.0.200/serde/",
+            ),
+            (
+                "http://docs.rs/serde/1.0.200/serde/trait.Serialize.html",
+                "https://docs.rs/serde/1.0.200/serde/trait.Serialize.html",
+            ),
+            (
+                "HTTP://DOC.RUST-LANG.ORG/std//vec/struct.Vec.html#method.push",
+                "https://doc.rust-lang.org/std/vec/struct.Vec.html#method.push",
+            ),
+            (
+                "https://docs.rs/serde/1.0.200/serde/de/index.html?search=x#examples",
+                "https://docs.rs/serde/1.0.200/serde/de/?search=x#examples",
+            ),
+            (
+                "http://example.org/api//serde/index.html",
+                "http://example.org/api/serde/",
+            ),
+            (
+                "https://example.org/serde/not-index.html",
+                "https://example.org/serde/not-index.html",
+            ),
+            (
+                "file:///home/user/target/doc/serde/index.html",
+                "file:///home/user/target/doc/serde/index.html",
+            ),
+        ] {
+            let url = url.parse().unwrap();
+            assert_eq!(normalize_url(url).as_str(), expected);
+        }
+    }
+
+    #[test]
+    fn test_pin_rust_version() {
+        for (url, expected) in [
+            (
+                "https://doc.rust-lang.org/std/vec/struct.Vec.html",
+                "https://doc.rust-lang.org/1.74.0/std/vec/struct.Vec.html",
+            ),
+            (
+                "https://doc.rust-lang.org/1.89.0/core/option/enum.Option.html#variant.Some",
+                "https://doc.rust-lang.org/1.74.0/core/option/enum.Option.html#variant.Some",
+            ),
+            (
+                "https://doc.rust-lang.org/nightly/alloc/index.html",
+                "https://doc.rust-lang.org/1.74.0/alloc/index.html",
+            ),
+            (
+                "https://doc.rust-lang.org/stable/proc_macro/",
+                "https://doc.rust-lang.org/1.74.0/proc_macro/",
+            ),
+            (
+                "https://doc.rust-lang.org/error_codes/E0277.html",
+                "https://doc.rust-lang.org/error_codes/E0277.html",
+            ),
+            (
+                "https://doc.rust-lang.org/stable/book/ch01-00.html",
+                "https://doc.rust-lang.org/stable/book/ch01-00.html",
+            ),
+            (
+                "https://docs.rs/serde/1.0.0/serde/",
+                "https://docs.rs/serde/1.0.0/serde/",
+            ),
+        ] {
+            let url = url.parse().unwrap();
+            assert_eq!(pin_rust_version(url, "1.74.0").as_str(), expected);
+        }
+
+        for (url, version, expected) in [
+            (
+                "https://doc.rust-lang.org/1.80/std/vec/struct.Vec.html",
+                "1.74.0",
+                "https://doc.rust-lang.org/1.74.0/std/vec/struct.Vec.html",
+            ),
+            (
+                "https://doc.rust-lang.org/std/vec/struct.Vec.html",
+                "1.80",
+                "https://doc.rust-lang.org/1.80.0/std/vec/struct.Vec.html",
+            ),
+            (
+                "https://doc.rust-lang.org/1.89.0/std/vec/struct.Vec.html",
+                "stable",
+                "https://doc.rust-lang.org/stable/std/vec/struct.Vec.html",
+            ),
+        ] {
+            let url = url.parse().unwrap();
+            assert_eq!(pin_rust_version(url, version).as_str(), expected);
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Result;
 use mdbook_preprocessor::PreprocessorContext;
@@ -6,13 +9,26 @@ use url::Url;
 
 use mdbookkit::{book::PreprocessorHelper, config::BaseDir, url::UrlFromPath};
 
-use crate::options::EnvConfig;
+use crate::{
+    docs_url::{normalize_url, pin_rust_version},
+    options::{EmitStyle, EnvConfig, FailOn, IgnoreItems, RewriteRule, UnresolvedLinks},
+};
 
 #[derive(Debug)]
 pub struct Environment {
     book_dir: PathBuf,
     page_dir: Url,
     base_dir: BaseDir,
+    crate_docs: BTreeMap<String, BaseDir>,
+    adopt_docs_urls: bool,
+    ignore_items: IgnoreItems,
+    rewrite: Vec<RewriteRule>,
+    normalize_urls: bool,
+    rust_version: Option<String>,
+    emit_style: EmitStyle,
+    html_attributes: BTreeMap<String, String>,
+    unresolved: UnresolvedLinks,
+    fail_on: FailOn,
 }
 
 impl Environment {
@@ -20,11 +36,24 @@ impl Environment {
         let book_dir = book.book_dir()?;
         let page_dir = book.page_dir()?;
         let base_dir = config.base_url.take().resolve(&page_dir);
+        let crate_docs = (config.crate_docs.into_iter())
+            .map(|(name, base_url)| (name, base_url.resolve(&page_dir)))
+            .collect();
         let page_dir = page_dir.dir_to_url();
         Ok(Self {
             book_dir,
             page_dir,
             base_dir,
+            crate_docs,
+            adopt_docs_urls: config.adopt_docs_urls,
+            ignore_items: config.ignore_items,
+            rewrite: config.rewrite,
+            normalize_urls: config.normalize_urls,
+            rust_version: config.rust_version,
+            emit_style: config.emit_style,
+            html_attributes: config.html_attributes,
+            unresolved: config.unresolved,
+            fail_on: config.fail_on,
         })
     }
 
@@ -39,6 +68,51 @@ impl Environment {
     pub fn base_dir(&self) -> &BaseDir {
         &self.base_dir
     }
+
+    /// The base URL for docs of a package, which may be set with `crate-docs`.
+    pub fn crate_base_dir(&self, name: &str) -> &BaseDir {
+        self.crate_docs.get(name).unwrap_or(&self.base_dir)
+    }
+
+    pub fn adopt_docs_urls(&self) -> bool {
+        self.adopt_docs_urls
+    }
+
+    pub fn ignore_items(&self) -> &IgnoreItems {
+        &self.ignore_items
+    }
+
+    pub fn emit_style(&self) -> EmitStyle {
+        self.emit_style
+    }
+
+    pub fn html_attributes(&self) -> &BTreeMap<String, String> {
+        &self.html_attributes
+    }
+
+    pub fn unresolved(&self) -> UnresolvedLinks {
+        self.unresolved
+    }
+
+    pub fn fail_on(&self) -> &FailOn {
+        &self.fail_on
+    }
+
+    /// Normalize a resolved URL if `normalize-urls` is enabled, pin it to `rust-version`
+    /// if set, and then apply all `rewrite` rules to it, in the order they are specified.
+    pub fn rewrite_url(&self, url: Url) -> Url {
+        let url = if self.normalize_urls {
+            normalize_url(url)
+        } else {
+            url
+        };
+        let url = if let Some(version) = &self.rust_version {
+            pin_rust_version(url, version)
+        } else {
+            url
+        };
+        self.rewrite.iter().fold(url, |url, rule| rule.apply(url))
+    }
 }
 
 #[cfg(test)]
@@ -53,6 +127,88 @@ impl Default for Environment {
             page_dir: page_dir.dir_to_url(),
             book_dir: page_dir,
             base_dir,
+            crate_docs: Default::default(),
+            adopt_docs_urls: false,
+            ignore_items: Default::default(),
+            rewrite: Default::default(),
+            normalize_urls: false,
+            rust_version: None,
+            emit_style: Default::default(),
+            html_attributes: Default::default(),
+            unresolved: Default::default(),
+            fail_on: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde::Deserialize;
+    use url::Url;
+
+    use crate::options::RewriteRule;
+
+    use super::Environment;
+
+    #[derive(Deserialize)]
+    struct Rules {
+        rewrite: Vec<RewriteRule>,
+    }
+
+    fn env(normalize_urls: bool, rust_version: Option<&str>, rules: &str) -> Result<Environment> {
+        let Rules { rewrite } = toml::from_str(&format!("rewrite = {rules}"))?;
+        Ok(Environment {
+            rewrite,
+            normalize_urls,
+            rust_version: rust_version.map(Into::into),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_rewrite_url() -> Result<()> {
+        let rules = r#"[
+            { match = '^https://doc\.rust-lang\.org/1\.80\.0/', replace = "https://rust.example.org/" },
+            { match = '^https://rust\.example\.org/(\w+)/', replace = "https://mirror.example.org/$1-docs/" },
+            { match = '/nightly/', replace = "/beta/" },
+            { match = '^https://docs\.rs/', replace = "not a url" },
+        ]"#;
+
+        for (normalize_urls, rust_version, url, expected) in [
+            (
+                // normalized and pinned before any rules apply, and then
+                // rewritten by each rule in order
+                true,
+                Some("1.80"),
+                "http://doc.rust-lang.org/nightly/std//vec/index.html",
+                "https://mirror.example.org/std-docs/vec/",
+            ),
+            (
+                false,
+                Some("1.80.0"),
+                "https://doc.rust-lang.org/stable/core/option/enum.Option.html",
+                "https://mirror.example.org/core-docs/option/enum.Option.html",
+            ),
+            (
+                false,
+                None,
+                "https://doc.rust-lang.org/nightly/std/vec/struct.Vec.html",
+                "https://doc.rust-lang.org/beta/std/vec/struct.Vec.html",
+            ),
+            (
+                // rewrites to invalid URLs are ignored
+                false,
+                None,
+                "https://docs.rs/serde/latest/serde/",
+                "https://docs.rs/serde/latest/serde/",
+            ),
+        ] {
+            let env = env(normalize_urls, rust_version, rules)?;
+            let actual = env.rewrite_url(url.parse::<Url>()?);
+            assert_eq!(actual.as_str(), expected, "{url:?}");
+        }
+
+        Ok(())
+    }
+}
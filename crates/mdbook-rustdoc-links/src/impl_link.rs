@@ -0,0 +1,265 @@
+use percent_encoding::percent_decode_str;
+
+/// Rewrite a link to a trait implementation as a link to the implementing type,
+/// with a fragment pointing to the `impl` block on the type's page.
+///
+/// Supported forms are `impl:Trait for Type`, `impl:Type` (inherent impls),
+/// and `<Type as Trait>`. Fully-qualified paths such as `<Type as Trait>::item`
+/// instead link to the item itself on the type's page, such as `#method.item`.
+///
+/// Returns [`None`] if the destination is not a link to an impl.
+pub fn impl_link_dest(dest: &str) -> Option<String> {
+    let dest = percent_decode_str(dest).decode_utf8().ok()?;
+    let dest = dest.trim();
+
+    let (of_trait, for_type, assoc) = if let Some(dest) = dest.strip_prefix("impl:") {
+        match split_top_level(dest, " for ") {
+            Some((of_trait, for_type)) => (Some(of_trait), for_type, None),
+            None if split_top_level(dest, " ").is_none() => (None, dest, None),
+            None => return None,
+        }
+    } else if let Some((dest, assoc)) = strip_qualified_item(dest)
+        && let Some((for_type, of_trait)) = split_top_level(dest, " as ")
+    {
+        (Some(of_trait), for_type, assoc)
+    } else {
+        return None;
+    };
+
+    let item = type_item_path(for_type)?;
+
+    let anchor = match (of_trait, assoc) {
+        (_, Some(assoc)) => assoc_item_anchor(assoc),
+        (Some(of_trait), None) => {
+            format!("impl-{}-for-{}", print_type(of_trait), print_type(for_type))
+        }
+        (None, None) => format!("impl-{}", print_type(for_type)),
+    };
+
+    Some(format!("{item}#{}", small_url_encode(&anchor)))
+}
+
+/// Extract `Type as Trait` and `item` from `<Type as Trait>` or `<Type as Trait>::item`.
+fn strip_qualified_item(dest: &str) -> Option<(&str, Option<&str>)> {
+    if !dest.starts_with('<') {
+        return None;
+    }
+    let mut depth = 0usize;
+    for (idx, c) in dest.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ => continue,
+        }
+        if depth == 0 {
+            let rest = &dest[idx + 1..];
+            let item = match rest.strip_prefix("::") {
+                Some(item)
+                    if !item.is_empty()
+                        && item.chars().all(|c| c.is_alphanumeric() || c == '_') =>
+                {
+                    Some(item)
+                }
+                Some(_) => return None,
+                None if rest.is_empty() => None,
+                None => return None,
+            };
+            return Some((&dest[1..idx], item));
+        }
+    }
+    None
+}
+
+/// The anchor of an associated item in a trait impl, guessed from how the item is named,
+/// since the kind of the item is not known until rustdoc resolves it.
+///
+/// If the type implements several traits with items of the same name, this is the
+/// anchor of the first one; rustdoc appends `-1`, `-2`, etc. to the others.
+fn assoc_item_anchor(item: &str) -> String {
+    if item.chars().any(|c| c.is_alphabetic())
+        && item.chars().all(|c| c.is_uppercase() || c.is_numeric() || c == '_')
+    {
+        format!("associatedconstant.{item}")
+    } else if item.starts_with(char::is_uppercase) {
+        format!("associatedtype.{item}")
+    } else {
+        format!("method.{item}")
+    }
+}
+
+/// Split at the first occurrence of `sep` that is not nested in brackets.
+fn split_top_level<'a>(text: &'a str, sep: &str) -> Option<(&'a str, &'a str)> {
+    let mut depth = 0usize;
+    for (idx, c) in text.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if depth == 0 && text[idx..].starts_with(sep) {
+            let (lhs, rhs) = (text[..idx].trim(), text[idx + sep.len()..].trim());
+            if lhs.is_empty() || rhs.is_empty() {
+                return None;
+            }
+            return Some((lhs, rhs));
+        }
+    }
+    None
+}
+
+/// The path of the implementing type as rustdoc would resolve it, without generics.
+fn type_item_path(ty: &str) -> Option<&str> {
+    let ty = ty.trim_start_matches('&').trim_start();
+    let ty = match ty.strip_prefix('\'') {
+        Some(ty) => ty.split_once(' ')?.1.trim_start(),
+        None => ty,
+    };
+    let ty = ty.strip_prefix("mut ").unwrap_or(ty).trim_start();
+    let path = ty.split('<').next()?.trim();
+    if !path.is_empty() && path.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ':') {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Print a type the way rustdoc does in impl anchors: paths are shortened to their
+/// last segment, and whitespace is normalized.
+fn print_type(ty: &str) -> String {
+    let mut out = String::with_capacity(ty.len());
+    let mut ident = String::new();
+
+    let flush = |ident: &mut String, out: &mut String| {
+        let name = ident.rsplit("::").next().unwrap_or_default();
+        out.push_str(name);
+        ident.clear();
+    };
+
+    for c in ty.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            ident.push(c);
+            continue;
+        }
+        flush(&mut ident, &mut out);
+        match c {
+            c if c.is_whitespace() => {
+                if !out.is_empty() && !out.ends_with([' ', '<', '(', '[']) {
+                    out.push(' ');
+                }
+            }
+            '>' | ')' | ']' | ',' | ';' => {
+                if out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push(c);
+                if matches!(c, ',' | ';') {
+                    out.push(' ');
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    flush(&mut ident, &mut out);
+
+    out.trim_end().to_owned()
+}
+
+/// Port of `small_url_encode` in `librustdoc/html/render/mod.rs`, which rustdoc uses
+/// to generate the IDs of impl blocks.
+fn small_url_encode(s: &str) -> String {
+    fn dont_escape(c: u8) -> bool {
+        c.is_ascii_alphanumeric()
+            || matches!(
+                c,
+                b'-' | b'_'
+                    | b'.'
+                    | b','
+                    | b'~'
+                    | b'!'
+                    | b'\''
+                    | b'('
+                    | b')'
+                    | b'*'
+                    | b'/'
+                    | b';'
+                    | b':'
+                    | b'?'
+                    | b'='
+            )
+    }
+
+    let mut out = String::with_capacity(s.len());
+    for c in s.bytes() {
+        if dont_escape(c) {
+            out.push(c as char);
+        } else if c == b' ' {
+            out.push('+');
+        } else {
+            out.push_str(&format!("%{c:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::impl_link_dest;
+
+    #[test]
+    fn test_impl_link_dest() {
+        for (dest, expected) in [
+            (
+                "impl:From<String> for PathBuf",
+                Some("PathBuf#impl-From%3CString%3E-for-PathBuf"),
+            ),
+            (
+                "impl:From<std::string::String> for std::path::PathBuf",
+                Some("std::path::PathBuf#impl-From%3CString%3E-for-PathBuf"),
+            ),
+            (
+                "<PathBuf as From<String>>",
+                Some("PathBuf#impl-From%3CString%3E-for-PathBuf"),
+            ),
+            (
+                "<Vec<u8> as IntoIterator>::into_iter",
+                Some("Vec#method.into_iter"),
+            ),
+            (
+                "<Vec<u8> as IntoIterator>::IntoIter",
+                Some("Vec#associatedtype.IntoIter"),
+            ),
+            (
+                "<std::num::Wrapping<u8> as Bounded>::MAX_VALUE",
+                Some("std::num::Wrapping#associatedconstant.MAX_VALUE"),
+            ),
+            ("<Vec<u8> as IntoIterator>::into_iter()", None),
+            (
+                "impl:From%3CString%3E%20for%20PathBuf",
+                Some("PathBuf#impl-From%3CString%3E-for-PathBuf"),
+            ),
+            (
+                "impl:From<&str> for String",
+                Some("String#impl-From%3C%26str%3E-for-String"),
+            ),
+            (
+                "impl:From<&'a str> for Cow<'a, str>",
+                Some("Cow#impl-From%3C%26'a+str%3E-for-Cow%3C'a,+str%3E"),
+            ),
+            (
+                "impl:PartialEq<Vec<U,A2>> for Vec<T,  A1>",
+                Some("Vec#impl-PartialEq%3CVec%3CU,+A2%3E%3E-for-Vec%3CT,+A1%3E"),
+            ),
+            (
+                "impl:From<[T; N]> for Vec<T>",
+                Some("Vec#impl-From%3C%5BT;+N%5D%3E-for-Vec%3CT%3E"),
+            ),
+            ("impl:Vec<T, A>", Some("Vec#impl-Vec%3CT,+A%3E")),
+            ("impl:From<String> for", None),
+            ("impl:Debug for [T]", None),
+            ("Vec<T>", None),
+            ("std::vec::Vec", None),
+        ] {
+            assert_eq!(impl_link_dest(dest).as_deref(), expected, "{dest}");
+        }
+    }
+}
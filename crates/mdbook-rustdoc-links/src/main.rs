@@ -14,34 +14,41 @@ use mdbookkit::{
     env::TruthyStr,
     env_var,
     error::{ProgramExit, Show, WithDebugContext, has_severity},
-    logging::init_logging,
+    logging::{init_logging, set_quiet},
 };
 
 use self::{
     builder::build_docs,
     env::Environment,
     options::Config,
-    tracker::{ExportedPages, LinkTracker},
+    tracker::{ExportedPages, LinkTracker, Timings},
 };
 
 mod builder;
 mod diagnostics;
+mod docs_url;
 mod env;
+mod impl_link;
 mod markdown;
 mod options;
 mod subprocess;
 mod tracker;
 
 fn main() {
+    let Program {
+        command,
+        check,
+        quiet,
+    } = clap::Parser::parse();
+    set_quiet(quiet);
     init_logging();
     let _span = error_span!({ PREPROCESSOR_NAME }).entered();
-    let Program { command } = clap::Parser::parse();
     match command {
         Some(Command::Supports { .. }) => Ok(()),
         Some(Command::ValidateConfig) => {
             validate_config_examples::<Config>().or_else(emit_error!())
         }
-        None => mdbook(),
+        None => mdbook(check),
     }
     .exit()
 }
@@ -50,6 +57,12 @@ fn main() {
 struct Program {
     #[command(subcommand)]
     command: Option<Command>,
+    /// Check links without modifying the book.
+    #[arg(long)]
+    check: bool,
+    /// Only show warnings and errors.
+    #[arg(long, short)]
+    quiet: bool,
 }
 
 #[derive(clap::Subcommand, Debug, Clone)]
@@ -71,12 +84,17 @@ struct MarkdownCommand {
     files: Vec<PathBuf>,
 }
 
-fn mdbook() -> Result<(), ()> {
+fn mdbook(check: bool) -> Result<(), ()> {
     let (ctx, mut book) = book_from_stdin(env!("CARGO_PKG_HOMEPAGE")).or_else(emit_error!())?;
 
     let Config {
         builder,
         env,
+        chapters,
+        check: check_config,
+        link_report,
+        timings: print_timings,
+        stats: print_stats,
         fail_on_warnings,
     } = ctx
         .book_toml()
@@ -89,24 +107,37 @@ fn mdbook() -> Result<(), ()> {
 
     let mut tracker = LinkTracker::new(env);
 
-    ctx.for_each_page(&book, |path, content| {
-        info_span!("page_read", file = ?path.show()).in_scope(|| {
-            tracker
-                .read(content, path)
-                .context("failed to parse file as markdown")
-                .or_else(emit_error!())?;
-            Ok(())
+    let mut timings = Timings::default();
+
+    timings.measure("read", || {
+        ctx.for_each_page(&book, |path, content| {
+            if !chapters.is_included(tracker.env().page_dir(), &path) {
+                debug!(file = ?path.show(), "chapter excluded");
+                return Ok(());
+            }
+            info_span!("page_read", file = ?path.show()).in_scope(|| {
+                tracker
+                    .read(content, path)
+                    .context("failed to parse file as markdown")
+                    .or_else(emit_error!())?;
+                Ok(())
+            })
         })
     })?;
 
-    build_docs(builder.resolve(tracker.env().book_dir())?, &mut tracker)?;
+    timings.measure("build", || {
+        build_docs(builder.resolve(tracker.env().book_dir())?, &mut tracker)
+    })?;
 
     let ExportedPages {
         mut contents,
         issues,
-        stats,
+        mut stats,
         links,
-    } = tracker.export();
+        report,
+    } = timings.measure("export", || tracker.export());
+
+    stats.timings = timings;
 
     if MDBOOKKIT_LINK_REPORT.truthy().is_some() {
         info_span!("link-report").in_scope(|| {
@@ -116,6 +147,18 @@ fn mdbook() -> Result<(), ()> {
         });
     }
 
+    if let Some(path) = link_report {
+        let path = tracker.env().book_dir().join(path);
+        serde_json::to_string_pretty(&report)
+            .context("failed to serialize link report")
+            .and_then(|report| {
+                std::fs::write(&path, report + "\n")
+                    .with_context(|| format!("failed to write link report to {:?}", path.show()))
+            })
+            .or_else(emit_warning!())
+            .ok();
+    }
+
     for issues in IssueReporter::sorted(issues) {
         issues.emit(emit!());
     }
@@ -124,24 +167,50 @@ fn mdbook() -> Result<(), ()> {
 
     fail_on_warnings.check().or_else(emit_error!())?;
 
-    ctx.for_each_page_mut(&mut book, |path, content| {
-        let text = contents
-            .remove(&path)
-            .with_debug(&path, "file")
-            .expect("`contents` should contain path");
+    // the tracker borrows page text from the book, which is modified below, so
+    // copy what is still needed; `contents` and `stats` are owned and remain usable
+    let page_dir = tracker.env().page_dir().clone();
 
-        *content = text
-            .with_debug(&path, "file")
-            .context("error generating output for file")
-            .or_else(emit_error!())?;
-
-        Ok(())
-    })?;
+    if check || check_config {
+        debug!("check mode, book is not modified");
+    } else {
+        stats.timings.measure("write", || {
+            ctx.for_each_page_mut(&mut book, |path, content| {
+                if !chapters.is_included(&page_dir, &path) {
+                    return Ok(());
+                }
+
+                let text = contents
+                    .remove(&path)
+                    .with_debug(&path, "file")
+                    .expect("`contents` should contain path");
+
+                *content = text
+                    .with_debug(&path, "file")
+                    .context("error generating output for file")
+                    .or_else(emit_error!())?;
+
+                Ok(())
+            })
+        })?;
+    }
 
     ctx.print(book).or_else(emit_error!())?;
 
     info!("{stats}");
 
+    if print_stats {
+        info!("links by page:\n{}", stats.pages());
+    } else {
+        debug!("links by page:\n{}", stats.pages());
+    }
+
+    if print_timings || print_stats {
+        info!("timings: {}", stats.timings);
+    } else {
+        debug!("timings: {}", stats.timings);
+    }
+
     if has_severity(Level::WARN) {
         warn!("finished with warnings");
     } else {
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    collections::BTreeMap,
     fmt::Debug,
     path::{Path, PathBuf},
     process::Command,
@@ -7,20 +8,25 @@ use std::{
 
 use anyhow::{Context, Result, anyhow};
 use cargo_metadata::camino::Utf8PathBuf;
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
 use serde::{
     Deserialize, Deserializer,
     de::value::{MapAccessDeserializer, SeqAccessDeserializer},
 };
 use shlex::Shlex;
 use tap::{Pipe, Tap};
-use tracing::debug;
+use tracing::{debug, warn};
+use url::Url;
 
 use mdbookkit::{
     book::BookToml,
     config::{BaseUrl, UnstableFeature, ValueShorthand, value_or_vec, via},
+    diagnostics::{IssueLevel, IssueReport},
     de_struct, doc_link, emit_error,
     env::{is_ci, locate_project},
     error::FailOnWarnings,
+    impl_deserialize_from_str,
 };
 
 use crate::PREPROCESSOR_NAME;
@@ -39,8 +45,42 @@ de_struct!(
         env(EnvConfig(
             #[serde(default)]
             #[serde(deserialize_with = "via::<UnstableFeature<ValueShorthand<BaseUrl, _>>, _, _>")]
-            base_url as BaseUrlConfig
+            base_url as BaseUrlConfig,
+            #[serde(default)]
+            crate_docs,
+            #[serde(default)]
+            adopt_docs_urls,
+            #[serde(default)]
+            ignore_items,
+            #[serde(default)]
+            rewrite,
+            #[serde(default)]
+            normalize_urls,
+            #[serde(default)]
+            rust_version,
+            #[serde(default)]
+            emit_style,
+            #[serde(default)]
+            html_attributes,
+            #[serde(default)]
+            unresolved,
+            #[serde(default)]
+            fail_on
         )),
+        chapters(ChapterFilter(
+            #[serde(default)]
+            include,
+            #[serde(default)]
+            exclude
+        )),
+        #[serde(default)]
+        check,
+        #[serde(default)]
+        link_report,
+        #[serde(default)]
+        timings,
+        #[serde(default)]
+        stats,
         #[serde(default)]
         fail_on_warnings
     )
@@ -96,9 +136,28 @@ impl TryFrom<BookToml<'_>> for Config {
 pub struct Config {
     pub builder: BuilderConfig,
     pub env: EnvConfig,
+    pub chapters: ChapterFilter,
+    pub check: bool,
+    pub link_report: Option<PathBuf>,
+    pub timings: bool,
+    pub stats: bool,
     pub fail_on_warnings: FailOnWarnings,
 }
 
+/// Chapters to process, matched against their paths relative to the source directory.
+#[derive(Debug, Default)]
+pub struct ChapterFilter {
+    include: Option<Vec<ChapterGlob>>,
+    exclude: Vec<ChapterGlob>,
+}
+
+#[derive(Debug)]
+pub struct ChapterGlob(Pattern);
+
+impl_deserialize_from_str!(ChapterGlob, "a glob pattern", |s| {
+    Ok(Self(Pattern::new(s)?))
+});
+
 #[derive(Debug, Default)]
 pub struct BuilderConfig {
     manifest_dir: Option<Utf8PathBuf>,
@@ -168,6 +227,169 @@ pub struct CargoOptions {
 #[derive(Debug, Default)]
 pub struct EnvConfig {
     pub base_url: BaseUrlConfig,
+    /// Base URLs for specific packages, overriding `base_url`.
+    pub crate_docs: BTreeMap<String, BaseUrl>,
+    pub adopt_docs_urls: bool,
+    pub ignore_items: IgnoreItems,
+    pub rewrite: Vec<RewriteRule>,
+    pub normalize_urls: bool,
+    pub rust_version: Option<String>,
+    pub emit_style: EmitStyle,
+    pub html_attributes: BTreeMap<String, String>,
+    pub unresolved: UnresolvedLinks,
+    pub fail_on: FailOn,
+}
+
+/// How to report each kind of issue, overriding the level of the diagnostic.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FailOn {
+    #[serde(default)]
+    unresolved: Option<IssuePolicy>,
+    #[serde(default)]
+    rustdoc_warnings: Option<IssuePolicy>,
+    #[serde(default)]
+    unknown_fragments: Option<IssuePolicy>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum IssuePolicy {
+    Ignore,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum IssueCategory {
+    /// Links that rustdoc could not resolve.
+    Unresolved,
+    /// Other warnings from rustdoc, such as redundant explicit links.
+    RustdocWarnings,
+    /// Resolved links with a fragment that doesn't look like a rustdoc anchor.
+    /// Reported as notes by default.
+    UnknownFragments,
+}
+
+impl FailOn {
+    pub fn apply<'a>(
+        &self,
+        category: IssueCategory,
+        mut issue: IssueReport<'a>,
+    ) -> Option<IssueReport<'a>> {
+        let policy = match category {
+            IssueCategory::Unresolved => self.unresolved,
+            IssueCategory::RustdocWarnings => self.rustdoc_warnings,
+            IssueCategory::UnknownFragments => self.unknown_fragments,
+        };
+        // notes and help messages are never escalated, unless they are
+        // the issue itself, as is the case with unknown fragments
+        if issue.get_level() > IssueLevel::Warning
+            && !matches!(category, IssueCategory::UnknownFragments)
+        {
+            return Some(issue);
+        }
+        match policy {
+            None => {}
+            Some(IssuePolicy::Ignore) => return None,
+            Some(IssuePolicy::Warn) => {
+                issue.set_level(IssueLevel::Warning);
+            }
+            Some(IssuePolicy::Error) => {
+                issue.set_level(IssueLevel::Error);
+            }
+        }
+        Some(issue)
+    }
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmitStyle {
+    #[default]
+    Inline,
+    Reference,
+    Html,
+}
+
+/// What to do with item links that could not be resolved.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnresolvedLinks {
+    /// Leave the link as written.
+    #[default]
+    Keep,
+    /// Replace the link with its text, such that brackets are not rendered as-is.
+    Strip,
+}
+
+/// Replace parts of resolved URLs matching a regular expression.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RewriteRule {
+    #[serde(rename = "match")]
+    pattern: UrlPattern,
+    replace: String,
+}
+
+#[derive(Debug)]
+struct UrlPattern(Regex);
+
+impl_deserialize_from_str!(UrlPattern, "a regular expression", |s| {
+    let pattern = Regex::new(s).with_context(|| format!("invalid pattern {s:?}"))?;
+    Ok(Self(pattern))
+});
+
+impl RewriteRule {
+    pub fn apply(&self, url: Url) -> Url {
+        let Self {
+            pattern: UrlPattern(pattern),
+            replace,
+        } = self;
+        let rewritten = match pattern.replace(url.as_str(), replace) {
+            Cow::Borrowed(_) => return url,
+            Cow::Owned(rewritten) => rewritten,
+        };
+        match rewritten.parse() {
+            Ok(rewritten) => rewritten,
+            Err(error) => {
+                warn!("ignoring rewrite of {url} to invalid URL {rewritten:?}: {error}");
+                url
+            }
+        }
+    }
+}
+
+/// Link destinations that should be left as-is, either matched exactly,
+/// or matched as a regular expression if prefixed with `regex:`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(try_from = "Vec<String>")]
+pub struct IgnoreItems {
+    exact: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl IgnoreItems {
+    pub fn is_match(&self, dest: &str) -> bool {
+        self.exact.iter().any(|item| item == dest)
+            || self.patterns.iter().any(|pattern| pattern.is_match(dest))
+    }
+}
+
+impl TryFrom<Vec<String>> for IgnoreItems {
+    type Error = regex::Error;
+
+    fn try_from(value: Vec<String>) -> Result<Self, Self::Error> {
+        let mut items = Self::default();
+        for item in value {
+            if let Some(pattern) = item.strip_prefix("regex:") {
+                items.patterns.push(Regex::new(pattern)?);
+            } else {
+                items.exact.push(item);
+            }
+        }
+        Ok(items)
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -326,6 +548,33 @@ impl FeatureSelection {
     }
 }
 
+impl ChapterFilter {
+    pub fn is_included(&self, page_dir: &Url, page: &Url) -> bool {
+        let (Ok(page_dir), Ok(page)) = (page_dir.to_file_path(), page.to_file_path()) else {
+            return true;
+        };
+        let Ok(path) = page.strip_prefix(&page_dir) else {
+            return true;
+        };
+
+        const OPTIONS: MatchOptions = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        let matches = |ChapterGlob(glob): &ChapterGlob| glob.matches_path_with(path, OPTIONS);
+
+        if self.exclude.iter().any(matches) {
+            false
+        } else if let Some(include) = &self.include {
+            include.iter().any(matches)
+        } else {
+            true
+        }
+    }
+}
+
 impl BaseUrlConfig {
     pub fn take(self) -> BaseUrl {
         if is_ci().is_some() {
@@ -724,3 +973,133 @@ impl Debug for CustomCommand {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde::Deserialize;
+    use url::Url;
+
+    use mdbookkit::diagnostics::{IssueLevel, IssueReport};
+
+    use super::{ChapterFilter, FailOn, IgnoreItems, IssueCategory};
+
+    #[derive(Deserialize)]
+    struct Options<T> {
+        options: T,
+    }
+
+    fn parse<T: for<'de> Deserialize<'de>>(text: &str) -> Result<T> {
+        Ok(toml::from_str::<Options<T>>(&format!("options = {text}"))?.options)
+    }
+
+    #[test]
+    fn test_ignore_items() -> Result<()> {
+        let items = parse::<IgnoreItems>(r#"["Vec", "regex:^serde::__private::", "regex:Ext$"]"#)?;
+        for (dest, expected) in [
+            ("Vec", true),
+            ("std::vec::Vec", false),
+            ("Vec::new", false),
+            ("serde::__private::de::Content", true),
+            ("my_crate::serde::__private", false),
+            ("IteratorExt", true),
+            ("IteratorExt::map", false),
+            ("regex:Ext$", false),
+        ] {
+            assert_eq!(items.is_match(dest), expected, "{dest:?}");
+        }
+        assert!(parse::<IgnoreItems>(r#"["regex:("]"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_chapter_filter() -> Result<()> {
+        let page_dir = "file:///book/src/".parse::<Url>()?;
+        let is_included = |filter: &ChapterFilter, page: &str| {
+            filter.is_included(&page_dir, &page_dir.join(page).unwrap())
+        };
+
+        let filter = parse::<ChapterFilter>("{}")?;
+        assert!(is_included(&filter, "index.md"));
+        assert!(is_included(&filter, "api/index.md"));
+
+        let filter = parse::<ChapterFilter>(
+            r#"{ include = ["api/**", "*.md"], exclude = ["api/internal/*", "draft-*.md"] }"#,
+        )?;
+        for (page, expected) in [
+            ("index.md", true),
+            ("draft-intro.md", false),
+            ("guide/index.md", false),
+            ("api/index.md", true),
+            ("api/types/index.md", true),
+            ("api/internal/index.md", false),
+            ("api/internal/nested/index.md", true),
+        ] {
+            assert_eq!(is_included(&filter, page), expected, "{page:?}");
+        }
+
+        let filter = parse::<ChapterFilter>(r#"{ exclude = ["**/changelog.md"] }"#)?;
+        assert!(is_included(&filter, "index.md"));
+        assert!(!is_included(&filter, "changelog.md"));
+        assert!(!is_included(&filter, "release/changelog.md"));
+
+        assert!(parse::<ChapterFilter>(r#"{ include = ["[a-"] }"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fail_on() -> Result<()> {
+        use IssueCategory::*;
+        use IssueLevel::*;
+
+        for (config, cases) in [
+            (
+                "{}",
+                vec![
+                    (Unresolved, Warning, Some(Warning)),
+                    (RustdocWarnings, Warning, Some(Warning)),
+                    (UnknownFragments, Note, Some(Note)),
+                ],
+            ),
+            (
+                r#"{ unresolved = "error", rustdoc-warnings = "ignore" }"#,
+                vec![
+                    (Unresolved, Warning, Some(Error)),
+                    // notes and help messages are never escalated
+                    (Unresolved, Note, Some(Note)),
+                    (Unresolved, Help, Some(Help)),
+                    (RustdocWarnings, Warning, None),
+                    (RustdocWarnings, Note, Some(Note)),
+                    (UnknownFragments, Note, Some(Note)),
+                ],
+            ),
+            (
+                r#"{ unresolved = "warn", unknown-fragments = "error" }"#,
+                vec![
+                    (Unresolved, Error, Some(Warning)),
+                    // unless they are the issue itself
+                    (UnknownFragments, Note, Some(Error)),
+                ],
+            ),
+            (
+                r#"{ unknown-fragments = "warn" }"#,
+                vec![(UnknownFragments, Note, Some(Warning))],
+            ),
+            (
+                r#"{ unknown-fragments = "ignore" }"#,
+                vec![(UnknownFragments, Note, None)],
+            ),
+        ] {
+            let fail_on = parse::<FailOn>(config)?;
+            for (category, level, expected) in cases {
+                let issue = IssueReport::level(level).title("issue").build();
+                let actual = fail_on.apply(category, issue).map(|i| i.get_level());
+                assert_eq!(actual, expected, "{config} {category:?} {level:?}");
+            }
+        }
+
+        assert!(parse::<FailOn>(r#"{ unresolved = "deny" }"#).is_err());
+        assert!(parse::<FailOn>(r#"{ broken-links = "error" }"#).is_err());
+        Ok(())
+    }
+}
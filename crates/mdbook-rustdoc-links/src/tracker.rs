@@ -4,6 +4,7 @@ use std::{
     fmt::{Debug, Display, Write},
     ops::{ControlFlow, Range},
     path::PathBuf,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result, bail};
@@ -11,7 +12,7 @@ use cargo_metadata::{
     Package,
     diagnostic::{Diagnostic, DiagnosticSpan},
 };
-use html_escape::decode_html_entities;
+use html_escape::{decode_html_entities, encode_double_quoted_attribute};
 use lol_html::{HtmlRewriter, element, text};
 use mdbook_markdown::pulldown_cmark::{
     CowStr, Event,
@@ -25,7 +26,6 @@ use tracing::{debug, instrument, trace, warn};
 use url::Url;
 
 use mdbookkit::{
-    config::BaseDir,
     diagnostics::{
         Highlight, IssueLevel, IssueReport, IssueReporter, Note, SourceCode, Suggestion,
         annotate_snippets::AnnotationKind,
@@ -42,8 +42,11 @@ use mdbookkit::{
 use crate::{
     builder::{BuildOutput, symlink_dir_all},
     diagnostics::{DiagnosticNotes, RustcDiagnostic, SourceMap, report_level},
+    docs_url::item_path_from_url,
     env::Environment,
+    impl_link::impl_link_dest,
     markdown::markdown,
+    options::{EmitStyle, IssueCategory, UnresolvedLinks},
 };
 
 #[derive(Debug)]
@@ -61,6 +64,13 @@ struct Page<'a> {
     base: Url,
     link_end: usize,
     trivia: Vec<Vec<Event<'a>>>,
+    unadopted: Vec<Range<usize>>,
+    /// Spans of skip markers, which are removed from the output.
+    markers: Vec<Range<usize>>,
+    /// Labels of link reference definitions in the page, lowercased.
+    labels: BTreeSet<String>,
+    /// Number of links skipped because they matched `ignore-items`.
+    ignored: usize,
 }
 
 #[derive(Debug)]
@@ -70,6 +80,7 @@ struct Link<'a> {
     span: SourceSpan,
     elem: Tag<'a>,
     inner_elem: Vec<Event<'a>>,
+    adopted: Option<CowStr<'a>>,
     normalized: NormalizedLink<'a>,
     diagnostics: Vec<Diagnostic>,
 }
@@ -107,16 +118,42 @@ impl<'a> LinkTracker<'a> {
 
         let mut state = State::Trivia(vec![]);
         let mut trivia = vec![];
+        let mut unadopted = vec![];
+        let mut markers = vec![];
+        let mut ignored = 0;
 
-        for (event, span) in markdown(text).into_offset_iter() {
+        let mut skip_next = false;
+        let mut skip_link = false;
+
+        let mut stream = markdown(text).into_offset_iter();
+
+        for (event, span) in stream.by_ref() {
             match &mut state {
-                State::Trivia(events) => match Link::try_open(text, &event, span) {
-                    None => events.push(event),
-                    Some(link) => {
-                        trivia.push(std::mem::take(events));
-                        state = State::Link(link);
+                State::Trivia(events) => {
+                    match &event {
+                        Event::Html(html) | Event::InlineHtml(html) if is_skip_marker(html) => {
+                            markers.push(span.clone());
+                            skip_next = true;
+                        }
+                        Event::Text(text) | Event::Code(text) if !text.trim().is_empty() => {
+                            skip_next = false;
+                        }
+                        _ => {}
                     }
-                },
+                    let adopted = if self.env.adopt_docs_urls() {
+                        Link::try_adopt(text, &event, span.clone(), &mut unadopted)
+                    } else {
+                        None
+                    };
+                    match adopted.or_else(|| Link::try_open(text, &event, span)) {
+                        None => events.push(event),
+                        Some(link) => {
+                            trivia.push(std::mem::take(events));
+                            skip_link = std::mem::take(&mut skip_next);
+                            state = State::Link(link);
+                        }
+                    }
+                }
 
                 State::Link(link) => match link.push(event, span)? {
                     ControlFlow::Continue(()) => {}
@@ -125,7 +162,17 @@ impl<'a> LinkTracker<'a> {
                             State::Link(link) => link,
                             State::Trivia(..) => unreachable!(),
                         };
-                        match link.normalized() {
+                        let link = if skip_link {
+                            debug!(dest = ?&**link.original_dest(), "link skipped");
+                            ControlFlow::Break(link)
+                        } else if self.env.ignore_items().is_match(link.original_dest()) {
+                            debug!(dest = ?&**link.original_dest(), "link ignored");
+                            ignored += 1;
+                            ControlFlow::Break(link)
+                        } else {
+                            link.normalized()
+                        };
+                        match link {
                             ControlFlow::Continue(link) => self.links.push(link),
 
                             ControlFlow::Break(link) => {
@@ -139,11 +186,19 @@ impl<'a> LinkTracker<'a> {
             }
         }
 
+        let labels = (stream.reference_definitions().iter())
+            .map(|(label, _)| label.to_lowercase())
+            .collect();
+
         self.pages.push(Page {
             text,
             base,
             link_end: self.links.len(),
             trivia,
+            unadopted,
+            markers,
+            labels,
+            ignored,
         });
 
         Ok(())
@@ -201,14 +256,17 @@ impl<'a> LinkTracker<'a> {
                     && let Some(link) = state.borrow_mut().link()
                     && !eq_escaped(link.dest(), &href)
                 {
-                    if let Ok(url) = resolve_url(self.env.base_dir(), &output, &href)
+                    if let Ok(url) = resolve_url(&self.env, &output, &href)
                         .with_debug(&*href, "URL")
                         .context("could not convert to a full URL")
                         .or_else(with_bug_report!(emit_warning))
                     {
-                        link.href = Some(url)
+                        link.href = Some(self.env.rewrite_url(url))
                     }
-                    if let Some(title) = elem.get_attribute("title") {
+                    // keep titles written by the user
+                    if link.title().is_empty()
+                        && let Some(title) = elem.get_attribute("title")
+                    {
                         *link.title_mut() = title.into()
                     }
                 }
@@ -317,46 +375,98 @@ impl<'a> LinkTracker<'a> {
                 .show_path(&page.base)
                 .to_string();
 
+            let report = export.report.entry(name.clone()).or_default();
+
             let source = SourceCode {
                 source_code: page.text,
-                source_path: name.into(),
+                source_path: name.clone().into(),
             };
 
+            let (resolved, unresolved) = (ctx.stats.resolved, ctx.stats.unresolved);
+
             let issues = links
                 .iter()
                 .flat_map(|link| ctx.diagnose(link))
+                .chain(page.unadopted_summary())
                 .chain(self.link_summary(links))
                 .collect();
 
+            ctx.stats.pages.push(PageStatistics {
+                name,
+                resolved: ctx.stats.resolved - resolved,
+                unresolved: ctx.stats.unresolved - unresolved,
+                ignored: page.ignored,
+            });
+
+            ctx.stats.ignored += page.ignored;
+
             export.issues.push(IssueReporter { issues, source });
 
             for link in links {
-                if let Some(href) = &link.href {
-                    export.links.insert(link.dest(), href.as_str());
+                let href = link.href.as_ref().map(Url::as_str);
+                if let Some(href) = href {
+                    export.links.insert(link.original_dest(), href);
                 }
+                report.insert(link.original_dest(), href);
             }
 
             let mut trivia = page.trivia.iter();
             let mut links = links.iter();
 
-            let stream = std::iter::from_fn(|| {
+            let mut emitter = Emitter {
+                style: self.env.emit_style(),
+                html_attributes: self.env.html_attributes(),
+                unresolved: self.env.unresolved(),
+                definitions: LinkDefinitions::new(&page.labels),
+            };
+
+            let mut stream = std::iter::from_fn(|| {
                 let (trivia, link) = match (trivia.next(), links.next()) {
                     (Some(trivia), Some(link)) => {
-                        let link = Some(link.export(&page.base));
+                        let link = Some(link.export(&page.base, &mut emitter));
                         (Some((Patch::Trivial(trivia.iter().cloned()), None)), link)
                     }
                     (Some(trivia), None) => {
                         (Some((Patch::Trivial(trivia.iter().cloned()), None)), None)
                     }
-                    (None, Some(link)) => (None, Some(link.export(&page.base))),
+                    (None, Some(link)) => (None, Some(link.export(&page.base, &mut emitter))),
                     (None, None) => return None,
                 };
                 Some(trivia.into_iter().chain(link))
             })
-            .flatten();
+            .flatten()
+            .peekable();
+
+            // remove skip markers, keeping patches in source order
+            let mut markers = page.markers.iter().cloned().peekable();
+            let stream = std::iter::from_fn(|| {
+                let before = match stream.peek() {
+                    Some((_, None)) => 0,
+                    Some((_, Some(span))) => span.start,
+                    None => usize::MAX,
+                };
+                match markers.next_if(|marker| marker.start < before) {
+                    Some(marker) => Some((Patch::Trivial([].iter().cloned()), Some(marker))),
+                    None => stream.next(),
+                }
+            });
 
             let text = patch_stream(page.text, stream).map_err(<_>::into);
 
+            let definitions = emitter.definitions;
+
+            let text = if definitions.is_empty() {
+                text
+            } else {
+                text.map(|mut text| {
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    write!(text, "\n{definitions}").expect_fmt();
+                    text
+                })
+            };
+
             export.contents.insert(page.base.clone(), text);
         }
 
@@ -392,6 +502,24 @@ impl<'a> LinkTracker<'a> {
         &self.env
     }
 
+    /// Find a resolved item whose path is a likely typo of `dest`.
+    fn similar_item(&self, dest: &str) -> Option<&str> {
+        fn leading(path: &str) -> Option<&str> {
+            path.split("::").next()
+        }
+        self.links
+            .iter()
+            .filter(|link| link.href.is_some())
+            .map(|link| &**link.dest())
+            .filter(|item| *item != dest && leading(item) == leading(dest))
+            .filter_map(|item| {
+                let distance = edit_distance(item, dest);
+                (distance <= 2).then_some((distance, item))
+            })
+            .min()
+            .map(|(_, item)| item)
+    }
+
     fn link_summary(&self, links: &'a [Link<'a>]) -> Option<IssueReport<'a>> {
         let resolved = links
             .iter()
@@ -422,9 +550,11 @@ pub struct ExportedPages<'a> {
     pub issues: Vec<IssueReporter<'a>>,
     pub stats: Statistics,
     pub links: BTreeMap<&'a str, &'a str>,
+    /// Destinations of links in each page, or [`None`] if unresolved.
+    pub report: BTreeMap<String, BTreeMap<&'a str, Option<&'a str>>>,
 }
 
-fn resolve_url(base: &BaseDir, output: &BuildOutput<'_>, href: &str) -> Result<Url> {
+fn resolve_url(env: &Environment, output: &BuildOutput<'_>, href: &str) -> Result<Url> {
     if let Ok(href) = href.parse::<Url>() {
         return Ok(href);
     }
@@ -439,6 +569,8 @@ fn resolve_url(base: &BaseDir, output: &BuildOutput<'_>, href: &str) -> Result<U
         bail!("unsupported link format")
     };
 
+    let base = env.crate_base_dir(name);
+
     let url = (base.as_http_url())
         .unwrap_or_else(|| base.as_file_url())
         .pattern_fill(|group| match group {
@@ -496,6 +628,49 @@ fn could_be_item_link(kind: LinkType, dest: &str) -> bool {
     true
 }
 
+/// Whether a URL fragment looks like an anchor that rustdoc generates, either for an
+/// associated item, a trait implementation, or a heading in the docs.
+fn is_rustdoc_anchor(fragment: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "method.",
+        "tymethod.",
+        "variant.",
+        "structfield.",
+        "associatedtype.",
+        "associatedconstant.",
+        "impl-",
+    ];
+    if PREFIXES.iter().any(|prefix| fragment.starts_with(prefix)) {
+        return true;
+    }
+    // headings are slugified: lowercased, with spaces replaced by `-`
+    !fragment.is_empty()
+        && fragment
+            .chars()
+            .all(|c| c == '-' || c == '_' || (c.is_alphanumeric() && !c.is_uppercase()))
+}
+
+/// Whether an item path goes through a module that is conventionally hidden from docs,
+/// such as `__private` or `sealed`.
+fn could_be_hidden(dest: &str) -> bool {
+    let path = dest.split_once('#').map_or(dest, |(path, _)| path);
+    let mut segments = path.split("::").collect::<Vec<_>>();
+    // the last segment is the item itself
+    segments.pop();
+    segments.iter().any(|segment| {
+        segment.starts_with("__") || matches!(*segment, "private" | "sealed")
+    })
+}
+
+/// Whether this is the `<!-- rustdoc-links: skip -->` comment, which
+/// tells the preprocessor to leave the next link as-is.
+fn is_skip_marker(html: &str) -> bool {
+    html.trim()
+        .strip_prefix("<!--")
+        .and_then(|html| html.strip_suffix("-->"))
+        .is_some_and(|comment| comment.trim() == "rustdoc-links: skip")
+}
+
 fn eq_escaped(original: &str, encoded: &str) -> bool {
     let decoded = match percent_decode_str(encoded).decode_utf8() {
         Ok(decoded) => decoded,
@@ -546,12 +721,18 @@ impl<'a> Link<'a> {
             return None;
         };
 
-        if !could_be_item_link(*link_type, dest_url) {
+        let adopted = if matches!(*link_type, link_class!(ignored_link)) {
+            None
+        } else {
+            impl_link_dest(dest_url).map(CowStr::from)
+        };
+
+        if adopted.is_none() && !could_be_item_link(*link_type, dest_url) {
             trace!(?span, dest = ?&**dest_url, "link ...");
             return None;
         }
 
-        trace!(?span, dest = ?&**dest_url, title = ?&**title, "link >>>");
+        trace!(?span, dest = ?&**dest_url, title = ?&**title, ?adopted, "link >>>");
         Some(Link {
             href: None,
             kind: *link_type,
@@ -562,13 +743,73 @@ impl<'a> Link<'a> {
             },
             elem: elem.clone(),
             inner_elem: Default::default(),
+            adopted,
             normalized: NormalizedLink::borrowed(&text[span]),
             diagnostics: Default::default(),
         })
     }
 
+    /// Open a link to a docs.rs or doc.rust-lang.org page as if its destination
+    /// were the item path that the page documents, such that it can be resolved again.
+    fn try_adopt(
+        text: &'a str,
+        event: &Event<'a>,
+        span: Range<usize>,
+        unadopted: &mut Vec<Range<usize>>,
+    ) -> Option<Self> {
+        let Event::Start(
+            elem @ Tag::Link {
+                link_type: link_type @ (link_class!(href_defined)),
+                dest_url,
+                ..
+            },
+        ) = event
+        else {
+            return None;
+        };
+
+        let url = dest_url.parse::<Url>().ok()?;
+
+        if !matches!(url.host_str(), Some("docs.rs" | "doc.rust-lang.org")) {
+            return None;
+        }
+
+        let Some(path) = item_path_from_url(&url) else {
+            debug!(?span, dest = ?&**dest_url, "could not adopt link");
+            unadopted.push(locate_text(text, dest_url).unwrap_or(span));
+            return None;
+        };
+
+        trace!(?span, dest = ?&**dest_url, ?path, "link adopted");
+
+        Some(Link {
+            href: None,
+            kind: *link_type,
+            span: SourceSpan {
+                full: span.clone(),
+                text: text.len()..0, // empty span
+                dest: locate_text(text, dest_url),
+            },
+            elem: elem.clone(),
+            inner_elem: Default::default(),
+            adopted: Some(path.into()),
+            normalized: NormalizedLink::borrowed(&text[span]),
+            diagnostics: Default::default(),
+        })
+    }
+
+    /// The destination to be resolved by rustdoc.
     #[inline]
     fn dest(&self) -> &CowStr<'a> {
+        if let Some(adopted) = &self.adopted {
+            return adopted;
+        }
+        self.original_dest()
+    }
+
+    /// The destination as written in the source.
+    #[inline]
+    fn original_dest(&self) -> &CowStr<'a> {
         match self.elem {
             Tag::Link { ref dest_url, .. } => dest_url,
             _ => unreachable!(),
@@ -652,7 +893,9 @@ impl<'a> Link<'a> {
             Inline | ReferenceUnknown => true,
             Reference | Collapsed | Shortcut => false,
             link_class!(ignored_link) => unreachable!(),
-        } && !normalized.as_ref().contains('\n');
+        } && !normalized.as_ref().contains(['\r', '\n'])
+            // adopted links must be rewritten with the item path
+            && self.adopted.is_none();
 
         if is_one_line {
             return ControlFlow::Continue(self);
@@ -720,8 +963,9 @@ impl<'a> Link<'a> {
             return ControlFlow::Break(self);
         };
 
-        // link text may still contain newlines
-        let text = text.replace('\n', " ");
+        // link text may still contain newlines, which could also be CR or CRLF;
+        // replacing them byte-for-byte keeps `span` valid
+        let text = text.replace(['\r', '\n'], " ");
         // this is acceptable even within `inline code`:
         // https://spec.commonmark.org/0.31.2/#code-spans
 
@@ -733,38 +977,101 @@ impl<'a> Link<'a> {
     fn export<T>(
         &'a self,
         base: &Url,
+        emitter: &mut Emitter<'a>,
     ) -> (
-        Patch<T, impl Iterator<Item = Event<'a>>, impl Iterator<Item = Event<'a>>>,
+        Patch<T, impl Iterator<Item = Event<'a>> + use<'a, T>, impl Iterator<Item = Event<'a>> + use<'a, T>>,
         Option<Range<usize>>,
     ) {
-        match self.export_modified(base) {
+        match self.export_modified(base, emitter) {
             Some(link) => (Patch::Updated(link), Some(self.span.full.clone())),
             None => (Patch::Skipped(self.export_original()), None),
         }
     }
 
-    fn export_modified(&'a self, base: &Url) -> Option<impl Iterator<Item = Event<'a>>> {
+    fn export_modified(
+        &'a self,
+        base: &Url,
+        emitter: &mut Emitter<'a>,
+    ) -> Option<impl Iterator<Item = Event<'a>> + use<'a>> {
         let Self {
             href, inner_elem, ..
         } = self;
 
-        let href = href.as_ref()?;
+        let (start, end) = if let Some(href) = href {
+            let (start, end) = self.link_events(base, href, emitter);
+            (Some(start), Some(end))
+        } else {
+            // only shortcut and reference links would otherwise be rendered
+            // with brackets; other links are left as-is
+            let is_bracketed = matches!(
+                self.kind,
+                ReferenceUnknown | CollapsedUnknown | ShortcutUnknown
+            );
+            if emitter.unresolved == UnresolvedLinks::Strip && is_bracketed {
+                (None, None)
+            } else {
+                return None;
+            }
+        };
+
+        let iter = (start.into_iter())
+            .chain(inner_elem.iter().cloned())
+            .chain(end);
+
+        Some(iter)
+    }
+
+    fn link_events(
+        &'a self,
+        base: &Url,
+        href: &'a Url,
+        emitter: &mut Emitter<'a>,
+    ) -> (Event<'a>, Event<'a>) {
         let href = if let Some(href) = base.as_base().make_relative(href) {
             href.consume_with(CowStr::from)
         } else {
             CowStr::Borrowed(href.as_str())
         };
 
-        let iter = std::iter::once(Event::Start(Tag::Link {
-            link_type: Inline,
-            dest_url: href,
-            title: self.title().clone(),
-            id: CowStr::Borrowed(""),
-        }))
-        .chain(inner_elem.iter().cloned())
-        .chain(std::iter::once(Event::End(TagEnd::Link)));
-
-        Some(iter)
+        match emitter.style {
+            EmitStyle::Inline => {
+                let link = Tag::Link {
+                    link_type: Inline,
+                    dest_url: href,
+                    title: self.title().clone(),
+                    id: CowStr::Borrowed(""),
+                };
+                (Event::Start(link), Event::End(TagEnd::Link))
+            }
+            EmitStyle::Reference => {
+                // the definition is emitted separately, so the link only needs the label
+                let id = (emitter.definitions).insert(
+                    self.dest(),
+                    href.clone(),
+                    self.title().clone(),
+                );
+                let link = Tag::Link {
+                    link_type: Reference,
+                    dest_url: href,
+                    title: CowStr::Borrowed(""),
+                    id,
+                };
+                (Event::Start(link), Event::End(TagEnd::Link))
+            }
+            EmitStyle::Html => {
+                let mut start = format!(r#"<a href="{}""#, encode_double_quoted_attribute(&*href));
+                if !self.title().is_empty() {
+                    let title = encode_double_quoted_attribute(&**self.title());
+                    write!(start, r#" title="{title}""#).expect_fmt();
+                }
+                for (name, value) in emitter.html_attributes {
+                    let value = encode_double_quoted_attribute(value);
+                    write!(start, r#" {name}="{value}""#).expect_fmt();
+                }
+                start.push('>');
+                (Event::InlineHtml(start.into()), Event::InlineHtml("</a>".into()))
+            }
+        }
     }
 
     fn export_original(&self) -> impl Iterator<Item = Event<'a>> {
@@ -774,6 +1081,72 @@ impl<'a> Link<'a> {
     }
 }
 
+/// Options for writing resolved links back to a page.
+struct Emitter<'a> {
+    style: EmitStyle,
+    html_attributes: &'a BTreeMap<String, String>,
+    unresolved: UnresolvedLinks,
+    definitions: LinkDefinitions<'a>,
+}
+
+/// Link reference definitions to be appended to a page, when links are emitted
+/// as reference links.
+struct LinkDefinitions<'a> {
+    /// Labels are matched case-insensitively, so they are keyed in lowercase.
+    defined: BTreeMap<String, (String, CowStr<'a>, CowStr<'a>)>,
+    existing: &'a BTreeSet<String>,
+}
+
+impl<'a> LinkDefinitions<'a> {
+    fn new(existing: &'a BTreeSet<String>) -> Self {
+        Self {
+            defined: Default::default(),
+            existing,
+        }
+    }
+
+    /// Add a definition, returning its label. Labels are derived from the item path,
+    /// and are suffixed with a number if the label is already taken.
+    fn insert(&mut self, item: &str, href: CowStr<'a>, title: CowStr<'a>) -> CowStr<'a> {
+        let item = item.replace('[', "\\[").replace(']', "\\]");
+        let mut label = item.clone();
+        let mut suffix = 0usize;
+        loop {
+            let key = label.to_lowercase();
+            if self.existing.contains(&key) {
+                // defined in the page
+            } else if let Some((label, defined_href, defined_title)) = self.defined.get(&key) {
+                if *defined_href == href && *defined_title == title {
+                    return label.clone().into();
+                }
+            } else {
+                self.defined.insert(key, (label.clone(), href, title));
+                return label.into();
+            }
+            suffix += 1;
+            label = format!("{item}-{suffix}");
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.defined.is_empty()
+    }
+}
+
+impl Display for LinkDefinitions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (label, href, title) in self.defined.values() {
+            write!(f, "[{label}]: <{href}>")?;
+            if !title.is_empty() {
+                let title = title.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, " \"{title}\"")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 enum Patch<T, S, L> {
     Trivial(T),
     Skipped(S),
@@ -803,6 +1176,36 @@ impl SourceSpan {
     }
 }
 
+impl Page<'_> {
+    fn unadopted_summary(&self) -> Option<IssueReport<'static>> {
+        if self.unadopted.is_empty() {
+            return None;
+        }
+
+        let annotations = (self.unadopted.iter())
+            .map(|span| {
+                Highlight::span(span.clone())
+                    .kind(AnnotationKind::Primary)
+                    .label("not the page of an item")
+                    .build()
+            })
+            .collect();
+
+        IssueReport::level(IssueLevel::Note)
+            .title(format! {
+                "{} left as-is",
+                plural!(self.unadopted.len(), "docs URL")
+            })
+            .annotations(annotations)
+            .notes(vec![Note::note({
+                "option `adopt-docs-urls` could not determine the item \
+                these URLs are pointing to"
+            })])
+            .build()
+            .pipe(Some)
+    }
+}
+
 struct IssueReportContext<'a> {
     tracker: &'a LinkTracker<'a>,
     notes: DiagnosticNotes,
@@ -812,6 +1215,7 @@ struct IssueReportContext<'a> {
 impl<'a> IssueReportContext<'a> {
     fn diagnose(&mut self, link: &'a Link<'a>) -> Vec<IssueReport<'a>> {
         let mut issues = Vec::with_capacity(link.diagnostics.len());
+        let mut categories = Vec::with_capacity(link.diagnostics.len());
         let mut seen = BTreeSet::new();
 
         for diagnostic in link.diagnostics.iter() {
@@ -837,6 +1241,9 @@ impl<'a> IssueReportContext<'a> {
 
             if is_unresolved {
                 self.augment_unresolved(link, &mut issue);
+                categories.push(IssueCategory::Unresolved);
+            } else {
+                categories.push(IssueCategory::RustdocWarnings);
             }
 
             issues.push(issue);
@@ -864,6 +1271,12 @@ impl<'a> IssueReportContext<'a> {
                     r"to escape `[` and `]` characters, add '\' before them like `\[` or `\]`"
                 }));
             };
+            if could_be_hidden(link.dest()) {
+                notes.push(Note::help({
+                    "this item could be `#[doc(hidden)]`, which cannot be linked to; \
+                    try linking to its public re-export instead"
+                }));
+            }
             notes.push(Note::help(doc_link!(
                 see = "faq#rustdoc-did-not-process-this-link"
             )));
@@ -880,9 +1293,38 @@ impl<'a> IssueReportContext<'a> {
                 .build();
 
             issues.push(issue);
+            categories.push(IssueCategory::Unresolved);
+        }
+
+        if resolved
+            && let Some((_, fragment)) = link.dest().split_once('#')
+            && !is_rustdoc_anchor(fragment)
+        {
+            let span = link.span.dest.as_ref().unwrap_or(&link.span.full);
+            let issue = IssueReport::level(IssueLevel::Note)
+                .title("link fragment may not exist")
+                .annotations(vec![
+                    Highlight::span(span.clone())
+                        .kind(AnnotationKind::Primary)
+                        .label(format!("`#{fragment}` does not look like a rustdoc anchor"))
+                        .build(),
+                ])
+                .notes(vec![Note::help(
+                    "rustdoc anchors are either headings in lowercase, like `#examples`, \
+                    or associated items, like `#method.push`",
+                )])
+                .build();
+
+            issues.push(issue);
+            categories.push(IssueCategory::UnknownFragments);
         }
 
-        issues
+        let fail_on = self.tracker.env.fail_on();
+
+        (issues.into_iter())
+            .zip(categories)
+            .filter_map(|(issue, category)| fail_on.apply(category, issue))
+            .collect()
     }
 
     fn augment_unresolved(&mut self, link: &Link<'_>, report: &mut IssueReport<'_>) {
@@ -907,6 +1349,18 @@ impl<'a> IssueReportContext<'a> {
             report.secondary(suggestion);
         }
 
+        if let Some(similar) = self.tracker.similar_item(link.dest()) {
+            let suggestion = IssueReport::level(IssueLevel::Help)
+                .title(format!("an item with a similar name exists: `{similar}`"))
+                .patches(if let Some(span) = &link.span.dest {
+                    vec![Suggestion::span(span.clone()).repl(similar.to_owned()).build()]
+                } else {
+                    vec![]
+                })
+                .build();
+            report.secondary(suggestion);
+        }
+
         let could_be_top_level = report.iter_labels().any(|label| {
             label.ends_with(" in scope") || label.contains(" in module `temporary_crate_")
         });
@@ -915,6 +1369,10 @@ impl<'a> IssueReportContext<'a> {
             report.note(Note::note(note));
         }
 
+        if could_be_top_level && let Some(note) = self.notes.note_features_not_enabled() {
+            report.note(Note::note(note));
+        }
+
         let specifies_crate = if link.dest().starts_with("crate::") {
             Some("crate")
         } else if link.dest().starts_with("self::") {
@@ -945,6 +1403,22 @@ impl<'a> IssueReportContext<'a> {
     }
 }
 
+/// Levenshtein distance between two strings, counted in chars.
+fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs = rhs.chars().collect::<Vec<_>>();
+    let mut row = (0..=rhs.len()).collect::<Vec<_>>();
+    for (i, l) in lhs.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, r) in rhs.iter().enumerate() {
+            let substitution = diagonal + usize::from(l != *r);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[rhs.len()]
+}
+
 fn has_error_code(diag: &Diagnostic, code: &str) -> bool {
     diag.code.as_ref().map(|c| c.code == code).unwrap_or(false)
 }
@@ -1056,11 +1530,50 @@ impl LexicographicOrd for DiagnosticKey<'_> {
     }
 }
 
+/// Counts of item links processed in a build, and the time spent in each phase.
 #[derive(Debug, Default)]
 pub struct Statistics {
+    pub resolved: usize,
+    pub unresolved: usize,
+    pub has_warnings: usize,
+    /// Links skipped because they matched `ignore-items`.
+    pub ignored: usize,
+    pub timings: Timings,
+    pages: Vec<PageStatistics>,
+}
+
+#[derive(Debug)]
+struct PageStatistics {
+    name: String,
     resolved: usize,
     unresolved: usize,
-    has_warnings: usize,
+    ignored: usize,
+}
+
+impl Statistics {
+    /// Number of resolved, unresolved, and ignored links in each page, as a table.
+    pub fn pages(&self) -> impl Display {
+        std::fmt::from_fn(|f| {
+            let width = (self.pages.iter())
+                .map(|page| page.name.chars().count())
+                .max()
+                .unwrap_or_default();
+            write!(f, "{:width$}  resolved  unresolved  ignored", "page")?;
+            for PageStatistics {
+                name,
+                resolved,
+                unresolved,
+                ignored,
+            } in &self.pages
+            {
+                write!(
+                    f,
+                    "\n{name:width$}  {resolved:>8}  {unresolved:>10}  {ignored:>7}"
+                )?;
+            }
+            Ok(())
+        })
+    }
 }
 
 impl Display for Statistics {
@@ -1069,6 +1582,9 @@ impl Display for Statistics {
             resolved,
             unresolved,
             has_warnings,
+            ignored,
+            timings: _,
+            pages: _,
         } = self;
         let processed = resolved + unresolved;
         write! { f,
@@ -1081,10 +1597,38 @@ impl Display for Statistics {
         if *has_warnings > 0 {
             write! { f, "; {}",plural!(has_warnings, "has warnings", "have warnings") }?
         }
+        if *ignored > 0 {
+            write!(f, "; {ignored} ignored")?
+        }
         Ok(())
     }
 }
 
+/// Time spent in each phase of the preprocessor, in the order they ran.
+#[derive(Debug, Default)]
+pub struct Timings(Vec<(&'static str, Duration)>);
+
+impl Timings {
+    pub fn measure<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        let elapsed = started.elapsed();
+        debug!(?elapsed, "finished {phase}");
+        self.0.push((phase, elapsed));
+        result
+    }
+}
+
+impl Display for Timings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.0.iter().map(|(_, elapsed)| *elapsed).sum::<Duration>();
+        for (phase, elapsed) in self.0.iter() {
+            write!(f, "{phase} {:.0}ms, ", elapsed.as_secs_f64() * 1000.0)?;
+        }
+        write!(f, "total {:.0}ms", total.as_secs_f64() * 1000.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -1101,7 +1645,7 @@ mod tests {
 
     use crate::env::Environment;
 
-    use super::{LinkTracker, SourceSpan};
+    use super::{LinkTracker, SourceSpan, could_be_hidden, edit_distance, is_rustdoc_anchor};
 
     fn print_link_spans(span: SourceSpan) -> IssueReport<'static> {
         let SourceSpan { full, text, dest } = span;
@@ -1131,26 +1675,59 @@ mod tests {
             .build()
     }
 
-    fn test_link_spans(text: &str, expected: Data) -> Result<()> {
+    fn test_link_spans(
+        text: &str,
+        page: Option<&str>,
+        resolve: bool,
+        expected: Data,
+    ) -> Result<()> {
         let mut tracker = LinkTracker::new(Environment::default());
         let root = tracker.env.page_dir();
-        let path = root.join("index.md").unwrap();
-        tracker.read(text, path)?;
-        let span = tracker.links[0].span.clone();
-        let source = SourceCode {
-            source_code: text,
-            source_path: "<anon>".into(),
+        let path = root.join(page.unwrap_or("index.md")).unwrap();
+        tracker.read(text, path.clone())?;
+
+        let input = tracker.rustdoc_input().unwrap_or_default();
+        assert!(!input.contains('\r'), "{input:?}");
+        assert_eq!(input.lines().count(), tracker.links.len(), "{input:?}");
+
+        if resolve {
+            for link in tracker.links.iter_mut() {
+                link.href = Some("https://doc.rust-lang.org/std/".parse()?);
+            }
+        }
+
+        let mut export = tracker.export();
+        let output = export.contents.remove(&path).unwrap()?;
+
+        let source_path = match page {
+            Some(_) => export.report.keys().next().unwrap().clone(),
+            None => "<anon>".into(),
         };
-        let report = print_link_spans(span);
-        let report = issue_to_report(report, source);
+
         let renderer = Renderer::styled().decor_style(DecorStyle::Ascii);
-        let actual = renderer.render(&report);
+        let mut actual = (tracker.links.iter())
+            .map(|link| {
+                let source = SourceCode {
+                    source_code: text,
+                    source_path: source_path.clone().into(),
+                };
+                let report = print_link_spans(link.span.clone());
+                renderer.render(&issue_to_report(report, source))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if output != text {
+            actual.push_str("\n\n");
+            actual.push_str(&output);
+        }
+
         default_assert().try_eq_text(None, actual, expected)?;
         Ok(())
     }
 
     macro_rules! test_link_spans {
-        ( $name:ident ( $($line:literal),* ) ) => {
+        ( $name:ident ( $($line:literal),* ) $(, $($opt:tt)* )? ) => {
             #[test]
             fn $name() -> Result<()> {
                 let path = current_dir!()
@@ -1158,9 +1735,62 @@ mod tests {
                     .join(concat!(stringify!($name), ".svg"));
                 let data = Data::read_from(&path, Some(DataFormat::TermSvg));
                 let text = concat!($($line, "\n"),*);
-                test_link_spans(text, data)
+                test_link_spans!(@run text, data, None, false $(, $($opt)*)?)
             }
         };
+        ( @run $text:ident, $data:ident, $page:expr, $resolve:expr ) => {
+            test_link_spans($text, $page, $resolve, $data)
+        };
+        ( @run $text:ident, $data:ident, $page:expr, $resolve:expr, page = $new:literal ) => {
+            test_link_spans($text, Some($new), $resolve, $data)
+        };
+        ( @run $text:ident, $data:ident, $page:expr, $resolve:expr, resolve ) => {
+            test_link_spans($text, $page, true, $data)
+        };
+    }
+
+    #[test]
+    fn test_rustdoc_anchor() {
+        for (fragment, expected) in [
+            ("examples", true),
+            ("performance", true),
+            ("method.push", true),
+            ("variant.Some", true),
+            ("associatedtype.Item", true),
+            ("impl-From%3CIpv6Addr%3E-for-IpAddr", true),
+            ("Examples", false),
+            ("push", true),
+            ("", false),
+        ] {
+            assert_eq!(is_rustdoc_anchor(fragment), expected, "{fragment:?}");
+        }
+    }
+
+    #[test]
+    fn test_could_be_hidden() {
+        for (dest, expected) in [
+            ("serde::__private::de::Content", true),
+            ("my_crate::sealed::Sealed", true),
+            ("my_crate::internal::Thing", false),
+            ("my_crate::Thing", false),
+            ("my_crate::private", false),
+            ("__private", false),
+        ] {
+            assert_eq!(could_be_hidden(dest), expected, "{dest:?}");
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        for (lhs, rhs, expected) in [
+            ("Vec::push", "Vec::push", 0),
+            ("Vec::puhs", "Vec::push", 2),
+            ("Vec::pus", "Vec::push", 1),
+            ("Option", "Result", 6),
+            ("", "std", 3),
+        ] {
+            assert_eq!(edit_distance(lhs, rhs), expected, "{lhs:?} {rhs:?}");
+        }
     }
 
     test_link_spans!(link_span_inline("[drop](drop)"));
@@ -1188,4 +1818,37 @@ mod tests {
     test_link_spans!(link_span_shortcut_with_inline_unmapped(
         "[PhantomData<fn()>]"
     ));
+
+    test_link_spans!(link_span_line_endings_crlf(
+        "[`Vec`\r\nis a vector](std::vec::Vec)\r"
+    ));
+    test_link_spans!(link_span_line_endings_cr("[`Vec`\ris a vector](std::vec::Vec)"));
+    test_link_spans!(link_span_line_endings_reference(
+        "[`Vec`\r\nis a vector][std::vec::Vec]\r"
+    ));
+
+    test_link_spans!(link_span_skip_marker(
+        "Run <!-- rustdoc-links: skip -->[`build.rs`] with [`Vec`]",
+        "",
+        "<!-- rustdoc-links: skip -->",
+        "",
+        "[`main`], then [`Option`]",
+        "",
+        "Also <!-- rustdoc-links: skip --> see [`String`]"
+    ));
+
+    test_link_spans!(link_span_smart_punctuation(
+        "Use [`Vec`]--or \"[`Option`]\"---and 'the [\"quoted\" one](std::vec::Vec)'.",
+        "Curly \u{201c}[`String`]\u{201d} \u{2014} [`str`]..."
+    ));
+
+    test_link_spans!(
+        link_span_nested_formatting(
+            "See [**the** `Vec` type](std::vec::Vec),",
+            "or [*`Option`* and <kbd>`Some`</kbd>][Option]."
+        ),
+        resolve
+    );
+
+    test_link_spans!(link_span_non_ascii_path("[`Vec`]"), page = "指南/入门.md");
 }
@@ -83,6 +83,11 @@ test_case![manifest_invalid, exit(101)];
 test_case![deserialize_workspace, exit(101)];
 test_case![deserialize_package, exit(101)];
 test_case![hidden_items, exit(0)];
+test_case![
+    ignore_items,
+    exit(0),
+    env = ["MDBOOK_LOG" = "warn,mdbook_rustdoc_links=info"]
+];
 test_case![
     fail_on_warnings_in_ci,
     exit(101),
@@ -321,5 +326,13 @@ fn redacted() -> Vec<(&'static str, RedactedValue)> {
                 .unwrap()
                 .into(),
         ), // windows
+        (
+            "[TIMINGS]",
+            Regex::new(r"timings: (?<redacted>.+)").unwrap().into(),
+        ),
+        (
+            "[ELAPSED]",
+            Regex::new(r"elapsed=(?<redacted>\S+)").unwrap().into(),
+        ),
     ]
 }
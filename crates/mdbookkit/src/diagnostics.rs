@@ -275,6 +275,17 @@ impl<'a> IssueReport<'a> {
         }
     }
 
+    #[inline]
+    pub fn get_level(&self) -> IssueLevel {
+        self.level
+    }
+
+    #[inline]
+    pub fn set_level(&mut self, level: IssueLevel) -> &mut Self {
+        self.level = level;
+        self
+    }
+
     #[inline]
     pub fn annotations(&mut self, item: Highlight<'a>) -> &mut Self {
         self.annotations.push(item);
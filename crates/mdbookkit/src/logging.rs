@@ -68,7 +68,10 @@
 use std::{
     fmt::{Debug, Display},
     io::Write,
-    sync::{Arc, LazyLock},
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 
 use console::StyledObject;
@@ -114,6 +117,15 @@ macro_rules! is_branded {
     }};
 }
 
+/// Only show warnings and errors, unless `MDBOOK_LOG` specifies otherwise.
+///
+/// This must be called before [`init_logging`] to take effect.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
 pub fn init_logging() {
     let logger = tracing_subscriber::fmt::layer()
         .compact()
@@ -145,8 +157,13 @@ pub fn init_logging() {
 }
 
 static ENV_FILTER: LazyLock<EnvFilterStore> = LazyLock::new(|| {
+    let default_level = if QUIET.load(Ordering::Relaxed) {
+        LevelFilter::WARN
+    } else {
+        LevelFilter::INFO
+    };
     let filter = EnvFilter::builder()
-        .with_default_directive(LevelFilter::INFO.into())
+        .with_default_directive(default_level.into())
         .parse_lossy(MDBOOK_LOG.as_deref().unwrap_or_default());
     let access = tracing_subscriber::registry().with(filter.clone());
     EnvFilterStore { access, filter }
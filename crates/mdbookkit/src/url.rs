@@ -306,7 +306,10 @@ impl UrlUtil for Url {
                 form_urlencoded::Serializer::new(String::new()),
                 |mut search, (k, v)| {
                     if let Some(v) = decode_group(v.as_ref()).and_then(&mut f) {
-                        search.append_pair(&k, &v);
+                        // a placeholder that expands to nothing removes the param
+                        if !v.is_empty() {
+                            search.append_pair(&k, &v);
+                        }
                     } else {
                         search.append_pair(&k, &v);
                     }
@@ -401,12 +404,20 @@ impl UrlUtil for Url {
                 }
             }
 
-            if !query_patterns.is_empty() {
-                // pattern has required params that the url being tested doesn't have
-                return None;
+            for (_, group) in query_patterns {
+                if let Some(group) = decode_group(&group) {
+                    // placeholder params that are absent are matched as empty,
+                    // mirroring `pattern_fill`
+                    matches.insert(group.to_owned().into(), Cow::Borrowed(""));
+                } else {
+                    // pattern has required params that the url being tested doesn't have
+                    return None;
+                }
             }
 
-            Some(query.finish().into())
+            Some(query.finish())
+                .filter(|q| !q.is_empty())
+                .map(<_>::into)
         } else {
             value.query().map(<_>::into)
         };
@@ -599,3 +610,81 @@ impl ToUtf8Path for PathBuf {
 }
 
 static UTF8_PATH_ERROR: &str = "path contains non-UTF-8 characters, which is unsupported";
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use url::Url;
+
+    use super::UrlUtil;
+
+    fn pattern() -> Url {
+        "https://dev.azure.com/org/project/_git/repo?path={path}&version={version}&_a=contents"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pattern_fill_empty_param() {
+        let url = pattern().pattern_fill(|group| match group {
+            "path" => Some(Cow::Borrowed("/src/lib.rs")),
+            "version" => Some(Cow::Borrowed("")),
+            _ => None,
+        });
+        assert_eq!(
+            url.as_str(),
+            "https://dev.azure.com/org/project/_git/repo?path=%2Fsrc%2Flib.rs&_a=contents"
+        );
+    }
+
+    #[test]
+    fn test_pattern_fill_param() {
+        let url = pattern().pattern_fill(|group| match group {
+            "path" => Some(Cow::Borrowed("/src/lib.rs")),
+            "version" => Some(Cow::Borrowed("GBmain")),
+            _ => None,
+        });
+        assert_eq!(
+            url.as_str(),
+            "https://dev.azure.com/org/project/_git/repo?path=%2Fsrc%2Flib.rs&version=GBmain&_a=contents"
+        );
+    }
+
+    #[test]
+    fn test_pattern_test_with_param() {
+        let pattern = pattern();
+        let url = "https://dev.azure.com/org/project/_git/repo?path=%2Fsrc%2Flib.rs&version=GBmain&_a=contents"
+            .parse()
+            .unwrap();
+        let matched = pattern.pattern_test(None, &url).unwrap();
+        assert_eq!(matched.matches["path"], "/src/lib.rs");
+        assert_eq!(matched.matches["version"], "GBmain");
+        assert_eq!(matched.query, None);
+    }
+
+    #[test]
+    fn test_pattern_test_without_param() {
+        let pattern = pattern();
+        let url = "https://dev.azure.com/org/project/_git/repo?path=%2Fsrc%2Flib.rs&_a=contents"
+            .parse()
+            .unwrap();
+        let matched = pattern.pattern_test(None, &url).unwrap();
+        assert_eq!(matched.matches["path"], "/src/lib.rs");
+        assert_eq!(matched.matches["version"], "");
+        assert_eq!(matched.query, None);
+    }
+
+    #[test]
+    fn test_pattern_test_constant_param() {
+        let pattern = pattern();
+        let url = "https://dev.azure.com/org/project/_git/repo?path=%2Fsrc%2Flib.rs"
+            .parse()
+            .unwrap();
+        assert!(pattern.pattern_test(None, &url).is_none());
+        let url = "https://dev.azure.com/org/project/_git/repo?path=%2Fsrc%2Flib.rs&_a=history"
+            .parse()
+            .unwrap();
+        assert!(pattern.pattern_test(None, &url).is_none());
+    }
+}